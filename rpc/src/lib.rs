@@ -0,0 +1,122 @@
+//  Copyright (c) 2019 Alain Brenzikofer
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! RPC interface for the Encointer Ceremonies module.
+
+use std::sync::Arc;
+use std::marker::PhantomData;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+use encointer_ceremonies::{MeetupIndexType, Reputation};
+use encointer_currencies::{CurrencyIdentifier, Location};
+use encointer_scheduler::CeremonyIndexType;
+
+pub use encointer_ceremonies_rpc_runtime_api::EncointerCeremoniesApi as EncointerCeremoniesRuntimeApi;
+
+#[rpc]
+pub trait EncointerCeremoniesApi<BlockHash, AccountId, Moment> {
+    #[rpc(name = "encointer_getMeetupIndex")]
+    fn get_meetup_index(&self, cid: CurrencyIdentifier, account: AccountId, at: Option<BlockHash>)
+        -> Result<MeetupIndexType>;
+
+    #[rpc(name = "encointer_getMeetupLocation")]
+    fn get_meetup_location(&self, cid: CurrencyIdentifier, meetup_index: MeetupIndexType, at: Option<BlockHash>)
+        -> Result<Option<Location>>;
+
+    #[rpc(name = "encointer_getMeetupTime")]
+    fn get_meetup_time(&self, cid: CurrencyIdentifier, meetup_index: MeetupIndexType, at: Option<BlockHash>)
+        -> Result<Option<Moment>>;
+
+    #[rpc(name = "encointer_getMeetupParticipants")]
+    fn get_meetup_participants(&self, cid: CurrencyIdentifier, account: AccountId, at: Option<BlockHash>)
+        -> Result<Vec<AccountId>>;
+
+    #[rpc(name = "encointer_getReputation")]
+    fn get_reputation(&self, cid: CurrencyIdentifier, cindex: CeremonyIndexType, account: AccountId, at: Option<BlockHash>)
+        -> Result<Reputation>;
+}
+
+/// error code for failing runtime calls
+const RUNTIME_ERROR: i64 = 1;
+
+fn runtime_error<E: std::fmt::Debug>(e: E) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: "Unable to query the encointer ceremonies runtime api.".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+/// implements the `EncointerCeremoniesApi` rpc on top of the runtime api
+pub struct EncointerCeremonies<C, Block> {
+    client: Arc<C>,
+    _marker: PhantomData<Block>,
+}
+
+impl<C, Block> EncointerCeremonies<C, Block> {
+    pub fn new(client: Arc<C>) -> Self {
+        EncointerCeremonies { client, _marker: Default::default() }
+    }
+}
+
+impl<C, Block, AccountId, Moment> EncointerCeremoniesApi<<Block as BlockT>::Hash, AccountId, Moment>
+    for EncointerCeremonies<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: EncointerCeremoniesRuntimeApi<Block, AccountId, Moment>,
+    AccountId: Codec,
+    Moment: Codec,
+{
+    fn get_meetup_index(&self, cid: CurrencyIdentifier, account: AccountId, at: Option<<Block as BlockT>::Hash>)
+        -> Result<MeetupIndexType> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.get_meetup_index(&at, cid, account).map_err(runtime_error)
+    }
+
+    fn get_meetup_location(&self, cid: CurrencyIdentifier, meetup_index: MeetupIndexType, at: Option<<Block as BlockT>::Hash>)
+        -> Result<Option<Location>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.get_meetup_location(&at, cid, meetup_index).map_err(runtime_error)
+    }
+
+    fn get_meetup_time(&self, cid: CurrencyIdentifier, meetup_index: MeetupIndexType, at: Option<<Block as BlockT>::Hash>)
+        -> Result<Option<Moment>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.get_meetup_time(&at, cid, meetup_index).map_err(runtime_error)
+    }
+
+    fn get_meetup_participants(&self, cid: CurrencyIdentifier, account: AccountId, at: Option<<Block as BlockT>::Hash>)
+        -> Result<Vec<AccountId>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.get_meetup_participants(&at, cid, account).map_err(runtime_error)
+    }
+
+    fn get_reputation(&self, cid: CurrencyIdentifier, cindex: CeremonyIndexType, account: AccountId, at: Option<<Block as BlockT>::Hash>)
+        -> Result<Reputation> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.get_reputation(&at, cid, cindex, account).map_err(runtime_error)
+    }
+}
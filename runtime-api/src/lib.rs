@@ -0,0 +1,43 @@
+//  Copyright (c) 2019 Alain Brenzikofer
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Runtime API definition for the Encointer Ceremonies module.
+//!
+//! Lets clients query their meetup assignment and reputation without
+//! recomputing meetup times or reading raw storage.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use rstd::prelude::*;
+
+use encointer_ceremonies::{MeetupIndexType, Reputation};
+use encointer_currencies::{CurrencyIdentifier, Location};
+use encointer_scheduler::CeremonyIndexType;
+
+sp_api::decl_runtime_apis! {
+    pub trait EncointerCeremoniesApi<AccountId, Moment> where
+        AccountId: Codec,
+        Moment: Codec,
+    {
+        /// the meetup `account` has been assigned to in the current ceremony. 0 if unassigned
+        fn get_meetup_index(cid: CurrencyIdentifier, account: AccountId) -> MeetupIndexType;
+        fn get_meetup_location(cid: CurrencyIdentifier, meetup_index: MeetupIndexType) -> Option<Location>;
+        /// only available during the ATTESTING phase
+        fn get_meetup_time(cid: CurrencyIdentifier, meetup_index: MeetupIndexType) -> Option<Moment>;
+        /// the fellow participants of the meetup `account` has been assigned to in the current ceremony
+        fn get_meetup_participants(cid: CurrencyIdentifier, account: AccountId) -> Vec<AccountId>;
+        fn get_reputation(cid: CurrencyIdentifier, cindex: CeremonyIndexType, account: AccountId) -> Reputation;
+    }
+}
@@ -0,0 +1,310 @@
+//  Copyright (c) 2019 Alain Brenzikofer
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Benchmarks for the Encointer Ceremonies module.
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use primitives::{crypto::KeyTypeId, sr25519};
+use sp_runtime::MultiSignature;
+use system::RawOrigin;
+
+const BENCH_KEY_TYPE: KeyTypeId = KeyTypeId(*b"bcer");
+const SEED: u32 = 0;
+
+/// a fresh keypair in the keystore. returns the account along with its public key for signing
+fn generate_account<T: Trait>() -> (T::AccountId, sr25519::Public) {
+    let public = runtime_io::crypto::sr25519_generate(BENCH_KEY_TYPE, None);
+    let account = T::AccountId::decode(&mut &public.encode()[..])
+        .expect("benchmarks require 32 byte account ids");
+    (account, public)
+}
+
+fn sign<T: Trait>(signer: &sr25519::Public, msg: &[u8]) -> T::Signature {
+    let signature = runtime_io::crypto::sr25519_sign(BENCH_KEY_TYPE, signer, msg)
+        .expect("key has been generated in the keystore");
+    T::Signature::decode(&mut &MultiSignature::from(signature).encode()[..])
+        .expect("benchmarks require MultiSignature")
+}
+
+/// registers a currency with `n_locations` meetup locations and the given bootstrappers
+fn setup_currency<T: Trait>(bootstrappers: Vec<T::AccountId>, n_locations: u64) -> CurrencyIdentifier {
+    let locations: Vec<Location> = (0..n_locations)
+        // a grid of 1 degree steps, so locations stay valid geolocations
+        .map(|i| Location { lat: Degree::from_num(i / 100), lon: Degree::from_num(i % 100) })
+        .collect();
+    let cid = CurrencyIdentifier::from(blake2_256(&(locations.clone(), bootstrappers.clone()).encode()));
+    <encointer_currencies::Module<T>>::new_currency(
+        RawOrigin::Signed(bootstrappers[0].clone()).into(),
+        locations,
+        bootstrappers,
+    ).expect("currency can be registered");
+    cid
+}
+
+/// registers `n` reputable participants for the current ceremony, bypassing the checks of `register_participant`
+fn register_reputables<T: Trait>(cid: CurrencyIdentifier, n: u32) {
+    let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+    for i in 1..=n {
+        let p: T::AccountId = account("participant", i, SEED);
+        let idx = i as ParticipantIndexType;
+        <ParticipantRegistry<T>>::insert((cid, cindex), &idx, &p);
+        <ParticipantIndex<T>>::insert((cid, cindex), &p, &idx);
+        <ParticipantReputation<T>>::insert((cid, cindex), &p, Reputation::UnverifiedReputable);
+    }
+    <ParticipantCount>::insert((cid, cindex), n as ParticipantIndexType);
+}
+
+/// links the reputation of a former key with a full reputation history and a last meetup
+/// to each of the first `n` registered participants, as if they had registered with a proof
+fn link_reputation<T: Trait>(cid: CurrencyIdentifier, n: u32) {
+    let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+    for i in 1..=n {
+        let p = Module::<T>::participant_registry((cid, cindex), &(i as ParticipantIndexType));
+        let attendee: T::AccountId = account("attendee", i, SEED);
+        <ParticipantReputation<T>>::insert((cid, cindex - 1), &attendee, Reputation::VerifiedLinked);
+        <ParticipantReputationProof<T>>::insert((cid, cindex), &p, ((cid, cindex - 1), attendee.clone()));
+        <LastMeetup<T>>::insert(cid, &attendee, (cindex - 1, 1));
+        fill_reputation_history::<T>(&attendee, cid);
+        fill_reputation_history::<T>(&p, cid);
+    }
+}
+
+/// fills an account's reputation history, so that updating it is as expensive as it gets
+fn fill_reputation_history<T: Trait>(who: &T::AccountId, cid: CurrencyIdentifier) {
+    let history: Vec<CurrencyCeremony> = (0..T::ReputationHistoryLength::get())
+        .map(|i| (cid, i as CeremonyIndexType))
+        .collect();
+    <ReputationHistory<T>>::insert(who, history);
+}
+
+fn next_phase<T: Trait>() {
+    let master = <encointer_scheduler::Module<T>>::ceremony_master();
+    <encointer_scheduler::Module<T>>::next_phase(RawOrigin::Signed(master).into())
+        .expect("ceremony master can progress phase");
+}
+
+benchmarks! {
+    _ { }
+
+    grant_reputation {
+        let (bootstrapper, _) = generate_account::<T>();
+        let cid = setup_currency::<T>(vec![bootstrapper], T::MeetupMaxSize::get());
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        let (reputable, _) = generate_account::<T>();
+        let origin = T::ReputationGrantOrigin::successful_origin();
+    }: {
+        Module::<T>::grant_reputation(origin, cid, cindex - 1, reputable)?;
+    }
+
+    approve_reputation_grant {
+        let threshold = T::BootstrapperApprovalThreshold::get().max(1);
+        let bootstrappers: Vec<T::AccountId> = (0..threshold).map(|i| account("bootstrapper", i, SEED)).collect();
+        let cid = setup_currency::<T>(bootstrappers.clone(), T::MeetupMaxSize::get());
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index() - 1;
+        let reputable: T::AccountId = account("reputable", 0, SEED);
+        // worst case: the last missing approval grants the reputation
+        if threshold > 1 {
+            <ReputationGrantApprovals<T>>::insert((cid, cindex), &reputable, bootstrappers[1..].to_vec());
+        }
+    }: _(RawOrigin::Signed(bootstrappers[0].clone()), cid, cindex, reputable)
+
+    register_participant {
+        let (bootstrapper, _) = generate_account::<T>();
+        let cid = setup_currency::<T>(vec![bootstrapper], T::MeetupMaxSize::get());
+        let (newbie, _) = generate_account::<T>();
+    }: _(RawOrigin::Signed(newbie), cid, None)
+
+    register_participant_with_proof {
+        let (bootstrapper, _) = generate_account::<T>();
+        let cid = setup_currency::<T>(vec![bootstrapper], T::MeetupMaxSize::get());
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        let (attendee, attendee_public) = generate_account::<T>();
+        let (prover, _) = generate_account::<T>();
+        <ParticipantReputation<T>>::insert((cid, cindex - 1), &attendee, Reputation::VerifiedUnlinked);
+        let proof = ProofOfAttendance {
+            prover_public: prover.clone(),
+            ceremony_index: cindex - 1,
+            currency_identifier: cid,
+            attendee_public: attendee,
+            attendee_signature: sign::<T>(&attendee_public, &(prover.clone(), cindex - 1).encode()),
+        };
+    }: register_participant(RawOrigin::Signed(prover), cid, Some(proof))
+
+    unregister_participant {
+        let (bootstrapper, _) = generate_account::<T>();
+        let cid = setup_currency::<T>(vec![bootstrapper], T::MeetupMaxSize::get());
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        let (attendee, attendee_public) = generate_account::<T>();
+        let (prover, _) = generate_account::<T>();
+        let (other, _) = generate_account::<T>();
+        <ParticipantReputation<T>>::insert((cid, cindex - 1), &attendee, Reputation::VerifiedUnlinked);
+        let proof = ProofOfAttendance {
+            prover_public: prover.clone(),
+            ceremony_index: cindex - 1,
+            currency_identifier: cid,
+            attendee_public: attendee,
+            attendee_signature: sign::<T>(&attendee_public, &(prover.clone(), cindex - 1).encode()),
+        };
+        // worst case: the proof link is reverted and the registry needs a swap
+        Module::<T>::register_participant(RawOrigin::Signed(prover.clone()).into(), cid, Some(proof))?;
+        Module::<T>::register_participant(RawOrigin::Signed(other).into(), cid, None)?;
+    }: _(RawOrigin::Signed(prover), cid)
+
+    register_attestations {
+        let n in 1 .. (T::MeetupMaxSize::get() - 1) as u32 => ();
+        let participants: Vec<(T::AccountId, sr25519::Public)> =
+            (0..=n).map(|_| generate_account::<T>()).collect();
+        let cid = setup_currency::<T>(participants.iter().map(|p| p.0.clone()).collect(), T::MeetupMaxSize::get());
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        next_phase::<T>();
+        next_phase::<T>();
+        // seat everybody at the first meetup, regardless of the assignment algorithm
+        let meetup: Vec<T::AccountId> = participants.iter().map(|p| p.0.clone()).collect();
+        <MeetupRegistry<T>>::insert((cid, cindex), 1, &meetup);
+        for p in meetup.iter() {
+            <MeetupIndex<T>>::insert((cid, cindex), p, 1);
+        }
+        <MeetupCount>::insert((cid, cindex), 1);
+
+        let claimant = participants[0].0.clone();
+        let location = Module::<T>::get_meetup_location(&cid, 1).expect("currency has locations");
+        let timestamp = Module::<T>::get_meetup_time(&cid, 1).expect("we are in ATTESTING phase");
+        let attestations: Vec<Attestation<T::Signature, T::AccountId, T::Moment>> = participants[1..]
+            .iter()
+            .map(|(attester, attester_public)| {
+                let claim = ClaimOfAttendance {
+                    claimant_public: claimant.clone(),
+                    ceremony_index: cindex,
+                    currency_identifier: cid,
+                    meetup_index: 1,
+                    location,
+                    timestamp,
+                    number_of_participants_confirmed: n + 1,
+                };
+                Attestation {
+                    signature: sign::<T>(attester_public, &claim.encode()),
+                    claim,
+                    public: attester.clone(),
+                }
+            })
+            .collect();
+    }: _(RawOrigin::Signed(claimant), attestations)
+
+    compose_meetups {
+        let n in 1 .. 1000 => ();
+        let (bootstrapper, _) = generate_account::<T>();
+        // enough locations to seat everybody
+        let cid = setup_currency::<T>(vec![bootstrapper], n as u64 / T::MeetupMinSize::get().max(1) + 1);
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        register_reputables::<T>(cid, n);
+        // worst case: everybody has linked reputation whose histories need to be merged
+        link_reputation::<T>(cid, n);
+    }: {
+        Module::<T>::compose_meetups(&cid, cindex);
+    }
+
+    seat_meetup {
+        let n in (T::MeetupMinSize::get() as u32) .. T::MeetupMaxSize::get() as u32 => ();
+        let (bootstrapper, _) = generate_account::<T>();
+        let cid = setup_currency::<T>(vec![bootstrapper], T::MeetupMaxSize::get());
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        register_reputables::<T>(cid, n);
+        // so few reputables all end up in the first meetup
+        Module::<T>::compose_meetups(&cid, cindex);
+    }: {
+        Module::<T>::seat_meetup(&cid, cindex, 1);
+    }
+
+    issue_rewards {
+        let n in 3 .. T::MeetupMaxSize::get() as u32 => ();
+        let (bootstrapper, _) = generate_account::<T>();
+        let cid = setup_currency::<T>(vec![bootstrapper], T::MeetupMaxSize::get());
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        register_reputables::<T>(cid, n);
+        let meetup: Vec<T::AccountId> = (1..=n as ParticipantIndexType)
+            .map(|i| Module::<T>::participant_registry((cid, cindex), &i))
+            .collect();
+        <MeetupRegistry<T>>::insert((cid, cindex), 1, &meetup);
+        <MeetupCount>::insert((cid, cindex), 1);
+        // everybody attests everybody else
+        for (i, p) in meetup.iter().enumerate() {
+            let idx = (i + 1) as AttestationIndexType;
+            let others: Vec<T::AccountId> = meetup.iter().filter(|o| *o != p).cloned().collect();
+            <MeetupIndex<T>>::insert((cid, cindex), p, 1);
+            <AttestationRegistry<T>>::insert((cid, cindex), &idx, &others);
+            <AttestationIndex<T>>::insert((cid, cindex), p, &idx);
+            <MeetupParticipantCountVote<T>>::insert((cid, cindex), p, &n);
+            fill_reputation_history::<T>(p, cid);
+        }
+        <AttestationCount>::insert((cid, cindex), n as AttestationIndexType);
+    }: {
+        Module::<T>::issue_rewards_for_meetup(&cid, cindex, 1);
+    }
+
+    set_ceremony_reward {
+        let origin = T::GovernanceOrigin::successful_origin();
+    }: {
+        Module::<T>::set_ceremony_reward(origin, BalanceType::from_num(1))?;
+    }
+
+    set_location_tolerance {
+        let origin = T::GovernanceOrigin::successful_origin();
+    }: {
+        Module::<T>::set_location_tolerance(origin, 1_000)?;
+    }
+
+    set_time_tolerance {
+        let origin = T::GovernanceOrigin::successful_origin();
+    }: {
+        Module::<T>::set_time_tolerance(origin, T::Moment::from(600_000u32))?;
+    }
+
+    set_currency_ceremony_reward {
+        let (bootstrapper, _) = generate_account::<T>();
+        let cid = setup_currency::<T>(vec![bootstrapper], T::MeetupMaxSize::get());
+        let origin = T::GovernanceOrigin::successful_origin();
+    }: {
+        Module::<T>::set_currency_ceremony_reward(origin, cid, Some(BalanceType::from_num(1)))?;
+    }
+
+    set_foreign_reputation_policy {
+        let n in 0 .. T::MaxWhitelistedCurrencies::get() => ();
+        let threshold = T::BootstrapperApprovalThreshold::get().max(1);
+        let bootstrappers: Vec<T::AccountId> = (0..threshold).map(|i| account("bootstrapper", i, SEED)).collect();
+        let cid = setup_currency::<T>(bootstrappers.clone(), T::MeetupMaxSize::get());
+        let mut whitelist: Vec<CurrencyIdentifier> = (0..n)
+            .map(|i| setup_currency::<T>(vec![account("foreign", i, SEED)], 1))
+            .collect();
+        whitelist.sort();
+        let policy = ForeignReputationPolicy::Whitelist(whitelist);
+        // worst case: the last missing approval sets the policy
+        if threshold > 1 {
+            <ForeignReputationPolicyApprovals<T>>::insert(&cid, (policy.clone(), bootstrappers[1..].to_vec()));
+        }
+    }: _(RawOrigin::Signed(bootstrappers[0].clone()), cid, policy)
+
+    purge_registry {
+        let n in 1 .. 1000 => ();
+        let (bootstrapper, _) = generate_account::<T>();
+        // enough locations to seat everybody
+        let cid = setup_currency::<T>(vec![bootstrapper], n as u64 / T::MeetupMinSize::get().max(1) + 1);
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        register_reputables::<T>(cid, n);
+        Module::<T>::assign_meetups_for(&cid, cindex, Weight::max_value());
+    }: {
+        Module::<T>::purge_registry_for(&cid, cindex, n);
+    }
+}
@@ -32,7 +32,8 @@ use support::{
     dispatch::DispatchResult,
     ensure,
     storage::{StorageDoubleMap, StorageMap},
-    traits::Get,
+    traits::{EnsureOrigin, Get, Randomness},
+    weights::{DispatchClass, FunctionOf, Weight},
 };
 use system::ensure_signed;
 
@@ -40,9 +41,13 @@ use rstd::{cmp::min, convert::TryInto};
 use rstd::prelude::*;
 
 use runtime_io::misc::{print_utf8, print_hex };
-use sp_runtime::traits::{IdentifyAccount, Member, Verify, CheckedSub};
+use runtime_io::hashing::blake2_256;
+use sp_runtime::traits::{IdentifyAccount, Member, Verify, CheckedSub, Saturating};
+use sp_runtime::Perbill;
 
 use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
 
 use encointer_currencies::{CurrencyIdentifier, Location, Degree, LossyInto};
 use encointer_balances::BalanceType;
@@ -57,9 +62,129 @@ pub trait Trait: system::Trait
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
     type Public: IdentifyAccount<AccountId = Self::AccountId>;
     type Signature: Verify<Signer = Self::Public> + Member + Decode + Encode;
+    /// source of randomness used to shuffle participants before meetup assignment
+    type RandomnessSource: Randomness<Self::Hash>;
+    /// meetups with fewer participants can't take place (default 3)
+    type MeetupMinSize: Get<u64>;
+    /// no meetup will have more participants than this (default 12)
+    type MeetupMaxSize: Get<u64>;
+    /// at most one out of `NewbieRatio` meetup participants may be a newbie (default 4)
+    type NewbieRatio: Get<u64>;
+    /// minimal number of matching votes on the number of meetup participants (default 3)
+    type MeetupQuorum: Get<u32>;
+    /// for how many ceremonies a proof of attendance can be used to register as reputable (default 1)
+    type ReputationLifetime: Get<CeremonyIndexType>;
+    /// weight that may be spent per block on the work caused by a ceremony phase change.
+    /// whatever exceeds it is carried over to the following blocks
+    type MaxPhaseChangeWeight: Get<Weight>;
+    /// how many registry entries of past ceremonies may be deleted per block
+    type MaxPurgedEntriesPerBlock: Get<u32>;
+    /// origin that may change the module's parameters
+    type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+    /// origin that may grant reputation without attendance, e.g. `EnsureCeremonyMaster`
+    type ReputationGrantOrigin: EnsureOrigin<Self::Origin>;
+    /// how many bootstrappers of a currency need to approve to grant reputation within that currency (default 2).
+    /// capped at the number of bootstrappers
+    type BootstrapperApprovalThreshold: Get<u32>;
+    /// how many times bootstrappers of a currency may grant reputation for a ceremony (default 10)
+    type MaxReputationGrantsPerCeremony: Get<u32>;
+    /// share of its weight an attendance retains for the reputation score with every
+    /// ceremony that has passed since (default 50%)
+    type ReputationDecay: Get<Perbill>;
+    /// how many of an account's most recent verified attendances are kept in its reputation history (default 12)
+    type ReputationHistoryLength: Get<u32>;
+    /// how many foreign currencies a currency may whitelist to accept their proofs of attendance (default 16)
+    type MaxWhitelistedCurrencies: Get<u32>;
+    type WeightInfo: WeightInfo;
 }
 
-const REPUTATION_LIFETIME: u32 = 1;
+/// weights of this module's dispatchables, as obtained by its benchmarks
+pub trait WeightInfo {
+    fn grant_reputation() -> Weight;
+    fn approve_reputation_grant() -> Weight;
+    fn register_participant() -> Weight;
+    fn register_participant_with_proof() -> Weight;
+    fn unregister_participant() -> Weight;
+    fn register_attestations(n: u32) -> Weight;
+    /// shuffling the `n` registered participants of a currency and splitting them into meetups
+    fn compose_meetups(n: u32) -> Weight;
+    /// seating the `n` participants of a meetup
+    fn seat_meetup(n: u32) -> Weight;
+    /// reward issuance for a meetup with `n` participants whose reputation histories are full
+    fn issue_rewards(n: u32) -> Weight;
+    /// deletion of `n` registry entries of a past ceremony
+    fn purge_registry(n: u32) -> Weight;
+    fn set_ceremony_reward() -> Weight;
+    fn set_location_tolerance() -> Weight;
+    fn set_time_tolerance() -> Weight;
+    fn set_currency_ceremony_reward() -> Weight;
+    /// setting a foreign reputation policy with a whitelist of `n` currencies
+    fn set_foreign_reputation_policy(n: u32) -> Weight;
+}
+
+/// placeholder weights for runtimes that haven't been benchmarked. they are estimates, not
+/// measurements: a flat base per call plus a share for every item whose cost grows with an argument.
+/// runtimes should derive their own `WeightInfo` from the benchmarks in `benchmarking.rs`
+impl WeightInfo for () {
+    fn grant_reputation() -> Weight { 10_000 }
+    fn approve_reputation_grant() -> Weight { 10_000 }
+    fn register_participant() -> Weight { 10_000 }
+    // verifying the signature of the proof of attendance dominates
+    fn register_participant_with_proof() -> Weight { 20_000 }
+    fn unregister_participant() -> Weight { 10_000 }
+    // every attestation needs a signature verification and a haversine distance
+    fn register_attestations(n: u32) -> Weight { 10_000u32.saturating_add(n.saturating_mul(10_000)) }
+    fn compose_meetups(n: u32) -> Weight { 10_000u32.saturating_add(n.saturating_mul(1_000)) }
+    fn seat_meetup(n: u32) -> Weight { 10_000u32.saturating_add(n.saturating_mul(1_000)) }
+    fn issue_rewards(n: u32) -> Weight { 10_000u32.saturating_add(n.saturating_mul(10_000)) }
+    fn purge_registry(n: u32) -> Weight { 10_000u32.saturating_add(n.saturating_mul(1_000)) }
+    fn set_ceremony_reward() -> Weight { 10_000 }
+    fn set_location_tolerance() -> Weight { 10_000 }
+    fn set_time_tolerance() -> Weight { 10_000 }
+    fn set_currency_ceremony_reward() -> Weight { 10_000 }
+    fn set_foreign_reputation_policy(n: u32) -> Weight { 10_000u32.saturating_add(n.saturating_mul(1_000)) }
+}
+
+/// ensures the origin is signed by the ceremony master of the scheduler
+pub struct EnsureCeremonyMaster<T>(rstd::marker::PhantomData<T>);
+impl<T: Trait> EnsureOrigin<T::Origin> for EnsureCeremonyMaster<T> {
+    type Success = T::AccountId;
+    fn try_origin(o: T::Origin) -> Result<Self::Success, T::Origin> {
+        o.into().and_then(|o| match o {
+            system::RawOrigin::Signed(who) if who == <encointer_scheduler::Module<T>>::ceremony_master() => Ok(who),
+            r => Err(T::Origin::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> T::Origin {
+        T::Origin::from(system::RawOrigin::Signed(<encointer_scheduler::Module<T>>::ceremony_master()))
+    }
+}
+
+/// reputation of accounts, for other modules to gate features on.
+/// answers are based on each account's reputation history, which only keeps the most recent
+/// `ReputationHistoryLength` attendances across all currencies and moves to the new key when
+/// an attendance is linked to a registration. the verification queries additionally consult
+/// the registry within the reputation lifetime, so they still know about attendances of the
+/// key that actually attended and about recent ones that have been truncated from its history.
+/// beyond the reputation lifetime, these attendances are not known anymore
+pub trait ReputationProvider<AccountId> {
+    /// whether an account's attendance at a completed ceremony of a currency has been verified
+    fn is_verified_at(cid: &CurrencyIdentifier, who: &AccountId, cindex: CeremonyIndexType) -> bool;
+    /// whether an account has been verified in a currency in any of the last
+    /// `within_ceremonies` completed ceremonies
+    fn is_verified(cid: &CurrencyIdentifier, who: &AccountId, within_ceremonies: CeremonyIndexType) -> bool;
+    /// in how many of the last `n` completed ceremonies an account's attendance has been verified.
+    /// if `cid` is given, only attendances in that currency count
+    fn attendance_count(who: &AccountId, cid: Option<CurrencyIdentifier>, n: CeremonyIndexType) -> u32;
+    /// a score that weighs recent verified attendances more heavily than older ones.
+    /// if `cid` is given, only attendances in that currency count
+    fn reputation_score(who: &AccountId, cid: Option<CurrencyIdentifier>) -> u64;
+}
+
+/// reputation score of an attendance at the last ceremony
+pub const FULL_ATTENDANCE_SCORE: u64 = 1_000_000;
 
 pub type ParticipantIndexType = u64;
 pub type MeetupIndexType = u64;
@@ -67,6 +192,7 @@ pub type AttestationIndexType = u64;
 pub type CurrencyCeremony = (CurrencyIdentifier, CeremonyIndexType);
 
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum Reputation {
     // no attestations for attendance claim
     Unverified,
@@ -82,6 +208,54 @@ impl Default for Reputation {
         Reputation::Unverified
     }
 }
+impl Reputation {
+    /// whether the attendance has been verified, regardless of whether it has been linked yet
+    pub fn is_verified(&self) -> bool {
+        match self {
+            Reputation::VerifiedUnlinked | Reputation::VerifiedLinked => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub enum ForeignReputationPolicy {
+    // proofs of attendance of any currency are accepted for registration
+    AcceptAll,
+    // only proofs of attendance of the same currency are accepted
+    LocalOnly,
+    // proofs of attendance of the same currency and the listed ones are accepted
+    Whitelist(Vec<CurrencyIdentifier>),
+}
+impl Default for ForeignReputationPolicy {
+    fn default() -> Self {
+        ForeignReputationPolicy::AcceptAll
+    }
+}
+
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AttestationRejectionReason {
+    // the attester hasn't been assigned to the claimant's meetup
+    NotAMeetupParticipant,
+    WrongCeremonyIndex,
+    WrongCurrencyIdentifier,
+    WrongMeetupIndex,
+    IllegalGeolocation,
+    BeyondLocationTolerance,
+    // claimed timestamp is before the meetup time, beyond time tolerance
+    TooEarly,
+    // claimed timestamp is after the meetup time, beyond time tolerance
+    TooLate,
+    BadSignature,
+}
+
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MeetupSkipReason {
+    // none of the participants has registered attestations
+    NoAttestations,
+    // not enough participants agree on how many people showed up
+    VotesNotDependable,
+}
 
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, Default, Debug)]
 pub struct Attestation<Signature, AccountId, Moment> {
@@ -119,12 +293,18 @@ decl_storage! {
         ParticipantIndex get(fn participant_index): double_map hasher(blake2_128_concat) CurrencyCeremony, hasher(blake2_128_concat) T::AccountId => ParticipantIndexType;
         ParticipantCount get(fn participant_count): map hasher(blake2_128_concat) CurrencyCeremony => ParticipantIndexType;
         ParticipantReputation get(fn participant_reputation): double_map hasher(blake2_128_concat) CurrencyCeremony, hasher(blake2_128_concat) T::AccountId => Reputation;
+        // the former attendance a participant has linked upon registration, so the link can be reverted
+        ParticipantReputationProof get(fn participant_reputation_proof): double_map hasher(blake2_128_concat) CurrencyCeremony, hasher(blake2_128_concat) T::AccountId => Option<(CurrencyCeremony, T::AccountId)>;
 
         // all meetups for each ceremony mapping to a vec of participants
         // caution: index starts with 1, not 0! (because null and 0 is the same for state storage)
         MeetupRegistry get(fn meetup_registry): double_map hasher(blake2_128_concat) CurrencyCeremony, hasher(blake2_128_concat) MeetupIndexType => Vec<T::AccountId>;
         MeetupIndex get(fn meetup_index): double_map hasher(blake2_128_concat) CurrencyCeremony, hasher(blake2_128_concat) T::AccountId => MeetupIndexType;
         MeetupCount get(fn meetup_count): map hasher(blake2_128_concat) CurrencyCeremony => MeetupIndexType;
+        // the last meetup each participant has been assigned to. survives registry purging
+        // so that assignment can avoid having the same people meet again. deleted along with
+        // the reputation of its ceremony
+        LastMeetup get(fn last_meetup): double_map hasher(blake2_128_concat) CurrencyIdentifier, hasher(blake2_128_concat) T::AccountId => Option<(CeremonyIndexType, MeetupIndexType)>;
 
         // collect fellow meetup participants accounts who attestationed key account
         // caution: index starts with 1, not 0! (because null and 0 is the same for state storage)
@@ -133,7 +313,42 @@ decl_storage! {
         AttestationCount get(fn attestation_count): map hasher(blake2_128_concat) CurrencyCeremony => AttestationIndexType;
         // how many peers does each participants observe at their meetup
         MeetupParticipantCountVote get(fn meetup_participant_count_vote): double_map hasher(blake2_128_concat) CurrencyCeremony, hasher(blake2_128_concat) T::AccountId => u32;
+        // verified attendances of each account, oldest first, at most `ReputationHistoryLength`.
+        // survives registry purging and moves along with linked reputation once registration is final
+        ReputationHistory get(fn reputation_history): map hasher(blake2_128_concat) T::AccountId => Vec<CurrencyCeremony>;
+
+        // bootstrappers who have approved to grant reputation to an account, until the threshold is reached
+        ReputationGrantApprovals get(fn reputation_grant_approvals): double_map hasher(blake2_128_concat) CurrencyCeremony, hasher(blake2_128_concat) T::AccountId => Vec<T::AccountId>;
+        // how many times bootstrappers have granted reputation for a ceremony
+        ReputationGrantCount get(fn reputation_grant_count): map hasher(blake2_128_concat) CurrencyCeremony => u32;
+
+        // phase changes whose work hasn't been completed yet, oldest first, along with the
+        // ceremony index at the time of the change and the index of the next currency to be processed
+        PendingPhaseChanges get(fn pending_phase_changes): Vec<(CeremonyPhaseType, CeremonyIndexType, u32)>;
+        // how many meetups of a ceremony have been seated so far, once its participants
+        // have been split into meetups
+        AssignmentCursor get(fn assignment_cursor): map hasher(blake2_128_concat) CurrencyCeremony => Option<MeetupIndexType>;
+        // past ceremonies whose registries still need to be deleted, oldest first
+        PendingPurges get(fn pending_purges): Vec<CurrencyCeremony>;
+        // past ceremonies whose reputation has expired and still needs to be deleted, oldest first
+        PendingExpiries get(fn pending_expiries): Vec<CurrencyCeremony>;
+        // accounts with reputation, reputation grant approvals or a last meetup from a ceremony,
+        // so these can be deleted once the ceremony's reputation expires
+        // caution: index starts with 1, not 0! (because null and 0 is the same for state storage)
+        ExpiringAccounts get(fn expiring_accounts): double_map hasher(blake2_128_concat) CurrencyCeremony, hasher(blake2_128_concat) ParticipantIndexType => T::AccountId;
+        ExpiringAccountCount get(fn expiring_account_count): map hasher(blake2_128_concat) CurrencyCeremony => ParticipantIndexType;
+        // past ceremonies whose rewards still need to be issued, oldest first
+        PendingRewards get(fn pending_rewards): Vec<CurrencyCeremony>;
+        // how many meetups of a past ceremony have been evaluated for rewards so far
+        RewardCursor get(fn reward_cursor): map hasher(blake2_128_concat) CurrencyCeremony => MeetupIndexType;
+
         CeremonyReward get(fn ceremony_reward) config(): BalanceType;
+        // which currencies' proofs of attendance a currency accepts for registration
+        ForeignReputationPolicies get(fn foreign_reputation_policy): map hasher(blake2_128_concat) CurrencyIdentifier => ForeignReputationPolicy;
+        // the policy bootstrappers of a currency are approving, along with who has approved so far
+        ForeignReputationPolicyApprovals get(fn foreign_reputation_policy_approvals): map hasher(blake2_128_concat) CurrencyIdentifier => Option<(ForeignReputationPolicy, Vec<T::AccountId>)>;
+        // overrides the default ceremony reward for a currency
+        CurrencyCeremonyReward get(fn currency_ceremony_reward) config(): map hasher(blake2_128_concat) CurrencyIdentifier => Option<BalanceType>;
         // [m] distance from assigned meetup location
         LocationTolerance get(fn location_tolerance) config(): u32; 
         // [ms] time tolerance for meetup moment
@@ -145,25 +360,75 @@ decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        #[weight = 10_000]
-        pub fn grant_reputation(origin, cid: CurrencyIdentifier, reputable: T::AccountId) -> DispatchResult {
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            let budget = T::MaxPhaseChangeWeight::get();
+            let mut weight = Self::process_pending_phase_changes(budget);
+            weight = weight.saturating_add(Self::issue_pending_rewards(budget.saturating_sub(weight)));
+            weight.saturating_add(Self::purge_pending(T::MaxPurgedEntriesPerBlock::get()))
+        }
+
+        /// grant reputation for a past ceremony whose reputation can still be used for registration
+        #[weight = T::WeightInfo::grant_reputation()]
+        pub fn grant_reputation(origin, cid: CurrencyIdentifier, cindex: CeremonyIndexType, reputable: T::AccountId) -> DispatchResult {
+            T::ReputationGrantOrigin::ensure_origin(origin)?;
+            ensure!(Self::is_within_reputation_lifetime(cindex),
+                <Error<T>>::CeremonyIndexBeyondReputationLifetime);
+            // a grant must not make an attendance that has already been linked linkable again
+            ensure!(!Self::participant_reputation((cid, cindex), &reputable).is_verified(),
+                <Error<T>>::AlreadyReputable);
+            Self::do_grant_reputation(cid, cindex, reputable);
+            Ok(())
+        }
+
+        /// approve to grant reputation within a currency as one of its bootstrappers.
+        /// reputation is granted once enough bootstrappers have approved
+        #[weight = T::WeightInfo::approve_reputation_grant()]
+        pub fn approve_reputation_grant(origin, cid: CurrencyIdentifier, cindex: CeremonyIndexType, reputable: T::AccountId) -> DispatchResult {
             let sender = ensure_signed(origin)?;
-            ensure!(sender == <encointer_scheduler::Module<T>>::ceremony_master(), "only the CeremonyMaster can call this function");
-            let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
-            <ParticipantReputation<T>>::insert(&(cid, cindex-1), reputable, Reputation::VerifiedUnlinked);
-            print_utf8(b"granting reputation to:");
-            print_hex(&sender.encode());
+            let bootstrappers = <encointer_currencies::Module<T>>::bootstrappers(cid);
+            ensure!(bootstrappers.contains(&sender), <Error<T>>::NotBootstrapper);
+            ensure!(Self::is_within_reputation_lifetime(cindex),
+                <Error<T>>::CeremonyIndexBeyondReputationLifetime);
+            ensure!(Self::participant_reputation((cid, cindex), &reputable) == Reputation::Unverified,
+                <Error<T>>::AlreadyReputable);
+            ensure!(Self::reputation_grant_count((cid, cindex)) < T::MaxReputationGrantsPerCeremony::get(),
+                <Error<T>>::TooManyReputationGrants);
+            let mut approvals = Self::reputation_grant_approvals((cid, cindex), &reputable);
+            ensure!(!approvals.contains(&sender), <Error<T>>::ReputationGrantAlreadyApproved);
+            approvals.push(sender.clone());
+            Self::deposit_event(RawEvent::ReputationGrantApproved(cid, cindex, reputable.clone(), sender));
+
+            let threshold = min(T::BootstrapperApprovalThreshold::get() as usize, bootstrappers.len());
+            if approvals.len() < threshold {
+                if approvals.len() == 1 {
+                    Self::add_expiring_account(&(cid, cindex), &reputable);
+                }
+                <ReputationGrantApprovals<T>>::insert((cid, cindex), &reputable, approvals);
+                return Ok(());
+            }
+            <ReputationGrantCount>::mutate((cid, cindex), |c| *c += 1);
+            Self::do_grant_reputation(cid, cindex, reputable.clone());
+            <ReputationGrantApprovals<T>>::remove((cid, cindex), &reputable);
             Ok(())
         }
 
-        #[weight = 10_000]
+        #[weight = FunctionOf(
+            |args: (&CurrencyIdentifier, &Option<ProofOfAttendance<T::Signature, T::AccountId>>)|
+                if args.1.is_some() {
+                    T::WeightInfo::register_participant_with_proof()
+                } else {
+                    T::WeightInfo::register_participant()
+                },
+            DispatchClass::Normal,
+            true
+        )]
         pub fn register_participant(origin, cid: CurrencyIdentifier, proof: Option<ProofOfAttendance<T::Signature, T::AccountId>>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(<encointer_scheduler::Module<T>>::current_phase() == CeremonyPhaseType::REGISTERING,
-                "registering participants can only be done during REGISTERING phase");
+                <Error<T>>::RegisteringOutsideRegisteringPhase);
 
             ensure!(<encointer_currencies::Module<T>>::currency_identifiers().contains(&cid),
-                "CurrencyIdentifier not found");
+                <Error<T>>::InexistentCurrency);
 
             let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
 
@@ -174,15 +439,19 @@ decl_module! {
             let count = <ParticipantCount>::get((cid, cindex));
 
             let new_count = count.checked_add(1).
-                ok_or("[EncointerCeremonies]: Overflow adding new participant to registry")?;
+                ok_or(<Error<T>>::RegistryOverflow)?;
+            let is_reputable = proof.is_some()
+                || <encointer_currencies::Module<T>>::bootstrappers(cid).contains(&sender);
             if let Some(p) = proof {
-                // we accept proofs from other currencies as well. no need to ensure cid
-                ensure!(sender == p.prover_public, "supplied proof is not proving sender");
-                ensure!(p.ceremony_index < cindex, "proof is acausal");
-                ensure!(p.ceremony_index >= cindex-REPUTATION_LIFETIME, "proof is outdated");
+                ensure!(sender == p.prover_public, <Error<T>>::WrongProofSubject);
+                // proofs from other currencies are accepted according to the currency's policy
+                ensure!(Self::accepts_reputation_of(&cid, &p.currency_identifier),
+                    <Error<T>>::ForeignReputationNotAccepted);
+                ensure!(p.ceremony_index < cindex, <Error<T>>::ProofAcausal);
+                ensure!(p.ceremony_index >= cindex.saturating_sub(T::ReputationLifetime::get()), <Error<T>>::ProofOutdated);
                 ensure!(Self::participant_reputation(&(p.currency_identifier, p.ceremony_index),
                     &p.attendee_public) == Reputation::VerifiedUnlinked,
-                    "former attendance has not been verified or has already been linked to other account");
+                    <Error<T>>::AttendanceUnverifiedOrAlreadyUsed);
                 if Self::verify_attendee_signature(p.clone()).is_err() {
                     return Err(<Error<T>>::BadProofOfAttendanceSignature.into());
                 };
@@ -193,99 +462,169 @@ decl_module! {
                 // register participant as reputable
                 <ParticipantReputation<T>>::insert((cid, cindex),
                     &sender, Reputation::UnverifiedReputable);
+                <ParticipantReputationProof<T>>::insert((cid, cindex), &sender,
+                    ((p.currency_identifier, p.ceremony_index), p.attendee_public));
             };
             <ParticipantRegistry<T>>::insert((cid, cindex), &new_count, &sender);
             <ParticipantIndex<T>>::insert((cid, cindex), &sender, &new_count);
             <ParticipantCount>::insert((cid, cindex), new_count);
             print_utf8(b"registered particiant:");
             print_hex(&sender.encode());
+            Self::deposit_event(RawEvent::ParticipantRegistered(cid, sender, is_reputable));
             Ok(())
         }
 
-        #[weight = 10_000]
-        pub fn register_attestations(origin, attestations: Vec<Attestation<T::Signature, T::AccountId, T::Moment>>) -> DispatchResult {
+        /// withdraw a registration for the upcoming ceremony. a linked proof of attendance
+        /// becomes available again to register with
+        #[weight = T::WeightInfo::unregister_participant()]
+        pub fn unregister_participant(origin, cid: CurrencyIdentifier) -> DispatchResult {
             let sender = ensure_signed(origin)?;
-            ensure!(<encointer_scheduler::Module<T>>::current_phase() == CeremonyPhaseType::ATTESTING,
-                "registering attestations can only be done during ATTESTING phase");
+            ensure!(<encointer_scheduler::Module<T>>::current_phase() == CeremonyPhaseType::REGISTERING,
+                <Error<T>>::RegisteringOutsideRegisteringPhase);
             let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
-            ensure!(attestations.len()>0, "empty attestations supplied");
-            let cid = attestations[0].claim.currency_identifier;
-            ensure!(<encointer_currencies::Module<T>>::currency_identifiers().contains(&cid),
-                "CurrencyIdentifier not found");
-
-            let meetup_index = Self::meetup_index((cid, cindex), &sender);
-            let mut meetup_participants = Self::meetup_registry((cid, cindex), &meetup_index);
-            ensure!(meetup_participants.contains(&sender), "origin not part of this meetup");
-            meetup_participants.retain(|x| x != &sender);
-            let num_registered = meetup_participants.len();
-            let num_signed = attestations.len();
-            ensure!(num_signed <= num_registered, "can\'t have more attestations than other meetup participants");
-            let mut verified_attestation_accounts = vec!();
-            let mut claim_n_participants = 0u32;
-
-            let mlocation = if let Some(l) = Self::get_meetup_location(&cid, meetup_index)
-                { l } else { return Err(<Error<T>>::MeetupLocationNotFound.into()) };
-            let mtime = if let Some(t) = Self::get_meetup_time(&cid, meetup_index)
-                { t } else { return Err(<Error<T>>::MeetupTimeCalculationError.into()) };
-            for w in 0..num_signed {
-                let attestation = &attestations[w];
-                let attestation_account = &attestations[w].public;
-                if meetup_participants.contains(attestation_account) == false {
-                    print_utf8(b"ignoring attestation that isn't a meetup participant");
-                    continue };
-                if attestation.claim.ceremony_index != cindex {
-                    print_utf8(b"ignoring claim with wrong ceremony index");
-                    continue };
-                if attestation.claim.currency_identifier != cid {
-                    print_utf8(b"ignoring claim with wrong currency identifier");
-                    continue };
-                if attestation.claim.meetup_index != meetup_index {
-                    print_utf8(b"ignoring claim with wrong meetup index");
-                    continue };
-                if !<encointer_currencies::Module<T>>::is_valid_geolocation(
-                    &attestation.claim.location) {
-                        print_utf8(b"ignoring claim with illegal geolocation");
-                        continue };   
-                if <encointer_currencies::Module<T>>::haversine_distance(
-                    &mlocation, &attestation.claim.location) > Self::location_tolerance() {
-                        print_utf8(b"ignoring claim beyond location tolerance");
-                        continue };   
-                if let Some(dt) = mtime.checked_sub(&attestation.claim.timestamp) {
-                    if dt > Self::time_tolerance() {
-                        print_utf8(b"ignoring claim beyond time tolerance (too early)");
-                        continue }; 
-                } else if let Some(dt) = attestation.claim.timestamp.checked_sub(&mtime) {
-                    if dt > Self::time_tolerance() {
-                        print_utf8(b"ignoring claim beyond time tolerance (too late)");
-                        continue }; 
-                }
-                if Self::verify_attestation_signature(attestation.clone()).is_err() {
-                    print_utf8(b"ignoring attestation with bad signature");
-                    continue };
-                // attestation is legit. insert it!
-                verified_attestation_accounts.insert(0, attestation_account.clone());
-                // is it a problem if this number isn't equal for all claims? Guess not.
-                claim_n_participants = attestation.claim.number_of_participants_confirmed;
+            ensure!(<ParticipantIndex<T>>::contains_key((cid, cindex), &sender),
+                <Error<T>>::ParticipantNotRegistered);
+
+            // swap-remove to keep the registry free of holes
+            let idx = <ParticipantIndex<T>>::take((cid, cindex), &sender);
+            let count = <ParticipantCount>::get((cid, cindex));
+            if idx != count {
+                let last = <ParticipantRegistry<T>>::get((cid, cindex), &count);
+                <ParticipantRegistry<T>>::insert((cid, cindex), &idx, &last);
+                <ParticipantIndex<T>>::insert((cid, cindex), &last, &idx);
             }
-            if verified_attestation_accounts.len() == 0 {
-                return Err(<Error<T>>::NoValidAttestations.into());
+            <ParticipantRegistry<T>>::remove((cid, cindex), &count);
+            <ParticipantCount>::insert((cid, cindex), count - 1);
+
+            <ParticipantReputation<T>>::remove((cid, cindex), &sender);
+            if let Some((cc, attendee)) = <ParticipantReputationProof<T>>::take((cid, cindex), &sender) {
+                // revert the burn so the former attendance can be linked again
+                <ParticipantReputation<T>>::insert(&cc, &attendee, Reputation::VerifiedUnlinked);
             }
+            print_utf8(b"unregistered participant:");
+            print_hex(&sender.encode());
+            Self::deposit_event(RawEvent::ParticipantUnregistered(cid, sender));
+            Ok(())
+        }
 
-            let count = <AttestationCount>::get((cid, cindex));
-            let mut idx = count+1;
+        /// register attestations of fellow meetup participants. rejected attestations are ignored
+        /// and reported with an `AttestationRejected` event each, as long as at least one attestation
+        /// is accepted. if none is, nothing is registered and the call fails with the reason the
+        /// first attestation was rejected for, without reporting the others
+        #[weight = FunctionOf(
+            |args: (&Vec<Attestation<T::Signature, T::AccountId, T::Moment>>,)|
+                T::WeightInfo::register_attestations(args.0.len() as u32),
+            DispatchClass::Normal,
+            true
+        )]
+        pub fn register_attestations(origin, attestations: Vec<Attestation<T::Signature, T::AccountId, T::Moment>>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::do_register_attestations(sender, attestations, false)
+        }
 
-            if <AttestationIndex<T>>::contains_key((cid, cindex), &sender) {
-                idx = <AttestationIndex<T>>::get((cid, cindex), &sender);
-            } else {
-                let new_count = count.checked_add(1).
-                    ok_or("[EncointerCeremonies]: Overflow adding new attestation to registry")?;
-                <AttestationCount>::insert((cid, cindex), new_count);
-            }
-            <AttestationRegistry<T>>::insert((cid, cindex), &idx, &verified_attestation_accounts);
-            <AttestationIndex<T>>::insert((cid, cindex), &sender, &idx);
-            <MeetupParticipantCountVote<T>>::insert((cid, cindex), &sender, &claim_n_participants);
-            print_utf8(b"registered attestations for:");
-            print_hex(&sender.encode());
+        /// like `register_attestations`, but fails if any of the attestations is rejected
+        #[weight = FunctionOf(
+            |args: (&Vec<Attestation<T::Signature, T::AccountId, T::Moment>>,)|
+                T::WeightInfo::register_attestations(args.0.len() as u32),
+            DispatchClass::Normal,
+            true
+        )]
+        pub fn register_attestations_strict(origin, attestations: Vec<Attestation<T::Signature, T::AccountId, T::Moment>>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::do_register_attestations(sender, attestations, true)
+        }
+
+        /// set the default reward for attending a ceremony
+        #[weight = T::WeightInfo::set_ceremony_reward()]
+        pub fn set_ceremony_reward(origin, reward: BalanceType) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            <CeremonyReward>::put(reward);
+            Self::deposit_event(RawEvent::CeremonyRewardUpdated(reward));
+            Ok(())
+        }
+
+        /// set the tolerated distance [m] of an attestation's location from the meetup location
+        #[weight = T::WeightInfo::set_location_tolerance()]
+        pub fn set_location_tolerance(origin, tolerance: u32) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            <LocationTolerance>::put(tolerance);
+            Self::deposit_event(RawEvent::LocationToleranceUpdated(tolerance));
+            Ok(())
+        }
+
+        /// set the tolerated deviation [ms] of an attestation's timestamp from the meetup time
+        #[weight = T::WeightInfo::set_time_tolerance()]
+        pub fn set_time_tolerance(origin, tolerance: T::Moment) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            <TimeTolerance<T>>::put(tolerance);
+            Self::deposit_event(RawEvent::TimeToleranceUpdated(tolerance));
+            Ok(())
+        }
+
+        /// set the reward for attending a ceremony of a currency. `None` falls back to the default reward
+        #[weight = T::WeightInfo::set_currency_ceremony_reward()]
+        pub fn set_currency_ceremony_reward(origin, cid: CurrencyIdentifier, reward: Option<BalanceType>) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(<encointer_currencies::Module<T>>::currency_identifiers().contains(&cid),
+                <Error<T>>::InexistentCurrency);
+            match reward {
+                Some(r) => <CurrencyCeremonyReward>::insert(&cid, r),
+                None => <CurrencyCeremonyReward>::remove(&cid),
+            }
+            Self::deposit_event(RawEvent::CurrencyCeremonyRewardUpdated(cid, reward));
+            Ok(())
+        }
+
+        /// set which currencies' proofs of attendance a currency accepts for registration.
+        /// governance sets the policy right away. bootstrappers of the currency approve it instead,
+        /// and it is set once enough of them have approved. approving a different policy
+        /// discards the approvals of the former one
+        #[weight = FunctionOf(
+            |args: (&CurrencyIdentifier, &ForeignReputationPolicy)|
+                T::WeightInfo::set_foreign_reputation_policy(match args.1 {
+                    ForeignReputationPolicy::Whitelist(w) => w.len().try_into().unwrap_or(u32::max_value()),
+                    _ => 0,
+                }),
+            DispatchClass::Normal,
+            true
+        )]
+        pub fn set_foreign_reputation_policy(origin, cid: CurrencyIdentifier, policy: ForeignReputationPolicy) -> DispatchResult {
+            let approver = match T::GovernanceOrigin::try_origin(origin) {
+                Ok(_) => None,
+                Err(origin) => Some(ensure_signed(origin)?),
+            };
+            let cids = <encointer_currencies::Module<T>>::currency_identifiers();
+            ensure!(cids.contains(&cid), <Error<T>>::InexistentCurrency);
+            let policy = match policy {
+                ForeignReputationPolicy::Whitelist(mut whitelist) => {
+                    whitelist.sort();
+                    whitelist.dedup();
+                    ensure!(whitelist.len() <= T::MaxWhitelistedCurrencies::get() as usize,
+                        <Error<T>>::TooManyWhitelistedCurrencies);
+                    ensure!(whitelist.iter().all(|c| cids.contains(c)), <Error<T>>::InexistentCurrency);
+                    ForeignReputationPolicy::Whitelist(whitelist)
+                }
+                p => p,
+            };
+            if let Some(sender) = approver {
+                let bootstrappers = <encointer_currencies::Module<T>>::bootstrappers(cid);
+                ensure!(bootstrappers.contains(&sender), <Error<T>>::NotBootstrapper);
+                let mut approvals = match Self::foreign_reputation_policy_approvals(&cid) {
+                    Some((p, approvals)) if p == policy => approvals,
+                    _ => vec![],
+                };
+                ensure!(!approvals.contains(&sender), <Error<T>>::ForeignReputationPolicyAlreadyApproved);
+                approvals.push(sender.clone());
+                Self::deposit_event(RawEvent::ForeignReputationPolicyApproved(cid, policy.clone(), sender));
+                let threshold = min(T::BootstrapperApprovalThreshold::get() as usize, bootstrappers.len());
+                if approvals.len() < threshold {
+                    <ForeignReputationPolicyApprovals<T>>::insert(&cid, (policy, approvals));
+                    return Ok(());
+                }
+            }
+            <ForeignReputationPolicyApprovals<T>>::remove(&cid);
+            <ForeignReputationPolicies>::insert(&cid, &policy);
+            Self::deposit_event(RawEvent::ForeignReputationPolicyUpdated(cid, policy));
             Ok(())
         }
     }
@@ -295,8 +634,47 @@ decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as system::Trait>::AccountId,
+        Moment = <T as timestamp::Trait>::Moment,
     {
-        ParticipantRegistered(AccountId),
+        /// a participant has registered for the upcoming ceremony. \[currency, who, is reputable\]
+        ParticipantRegistered(CurrencyIdentifier, AccountId, bool),
+        /// a participant has withdrawn their registration. \[currency, who\]
+        ParticipantUnregistered(CurrencyIdentifier, AccountId),
+        /// meetups have been assigned. \[currency, number of meetups, number of seated participants\]
+        MeetupsAssigned(CurrencyIdentifier, MeetupIndexType, ParticipantIndexType),
+        /// not all registrants of a currency could be seated because it has too few meetup locations.
+        /// \[currency, number of registrants left without a meetup\]
+        MeetupLocationsExhausted(CurrencyIdentifier, ParticipantIndexType),
+        /// attestations of fellow meetup participants have been registered.
+        /// \[currency, claimant, number of accepted attestations, number of ignored attestations\]
+        AttestationsRegistered(CurrencyIdentifier, AccountId, u32, u32),
+        /// an attestation has been ignored. \[currency, claimant, attester, reason\]
+        AttestationRejected(CurrencyIdentifier, AccountId, AccountId, AttestationRejectionReason),
+        /// a participant has been rewarded for attending a meetup. \[currency, who, reward\]
+        RewardIssued(CurrencyIdentifier, AccountId, BalanceType),
+        /// all meetups of a past ceremony have been evaluated for rewards. \[currency, ceremony index\]
+        RewardsFinalized(CurrencyIdentifier, CeremonyIndexType),
+        /// no rewards are issued for a meetup. \[currency, meetup index, reason\]
+        MeetupSkipped(CurrencyIdentifier, MeetupIndexType, MeetupSkipReason),
+        /// the default reward for attending a ceremony has changed. \[reward\]
+        CeremonyRewardUpdated(BalanceType),
+        /// the location tolerance has changed. \[tolerance in m\]
+        LocationToleranceUpdated(u32),
+        /// the time tolerance has changed. \[tolerance in ms\]
+        TimeToleranceUpdated(Moment),
+        /// the reward for attending a ceremony of a currency has changed. `None` means the
+        /// default reward applies. \[currency, reward\]
+        CurrencyCeremonyRewardUpdated(CurrencyIdentifier, Option<BalanceType>),
+        /// the policy on which foreign proofs of attendance a currency accepts has changed. \[currency, policy\]
+        ForeignReputationPolicyUpdated(CurrencyIdentifier, ForeignReputationPolicy),
+        /// a bootstrapper has approved a foreign reputation policy. \[currency, policy, bootstrapper\]
+        ForeignReputationPolicyApproved(CurrencyIdentifier, ForeignReputationPolicy, AccountId),
+        /// a bootstrapper has approved to grant reputation. \[currency, ceremony index, reputable, bootstrapper\]
+        ReputationGrantApproved(CurrencyIdentifier, CeremonyIndexType, AccountId, AccountId),
+        /// reputation has been granted without attendance. \[currency, ceremony index, who\]
+        ReputationGranted(CurrencyIdentifier, CeremonyIndexType, AccountId),
+        /// the registry of a past ceremony has been purged. \[currency, ceremony index\]
+        RegistryPurged(CurrencyIdentifier, CeremonyIndexType),
     }
 );
 
@@ -308,118 +686,522 @@ decl_error! {
         BadAttendeeSignature,
         MeetupLocationNotFound,
         MeetupTimeCalculationError,
-        NoValidAttestations
+        NoValidAttestations,
+        AttestationFromNonParticipant,
+        AttestationWithWrongCeremonyIndex,
+        AttestationWithWrongCurrencyIdentifier,
+        AttestationWithWrongMeetupIndex,
+        AttestationWithIllegalGeolocation,
+        AttestationBeyondLocationTolerance,
+        AttestationBeyondTimeTolerance,
+        RegisteringOutsideRegisteringPhase,
+        AttestationsOutsideAttestingPhase,
+        InexistentCurrency,
+        RegistryOverflow,
+        WrongProofSubject,
+        ProofAcausal,
+        ProofOutdated,
+        AttendanceUnverifiedOrAlreadyUsed,
+        NoAttestations,
+        NotMeetupParticipant,
+        TooManyAttestations,
+        SelfSignedAttestation,
+        ParticipantNotRegistered,
+        CeremonyIndexBeyondReputationLifetime,
+        NotBootstrapper,
+        AlreadyReputable,
+        TooManyReputationGrants,
+        ReputationGrantAlreadyApproved,
+        ForeignReputationNotAccepted,
+        TooManyWhitelistedCurrencies,
+        ForeignReputationPolicyAlreadyApproved
 	}
 }
 
+impl<T: Trait> From<AttestationRejectionReason> for Error<T> {
+    fn from(reason: AttestationRejectionReason) -> Self {
+        match reason {
+            AttestationRejectionReason::NotAMeetupParticipant => Error::<T>::AttestationFromNonParticipant,
+            AttestationRejectionReason::WrongCeremonyIndex => Error::<T>::AttestationWithWrongCeremonyIndex,
+            AttestationRejectionReason::WrongCurrencyIdentifier => Error::<T>::AttestationWithWrongCurrencyIdentifier,
+            AttestationRejectionReason::WrongMeetupIndex => Error::<T>::AttestationWithWrongMeetupIndex,
+            AttestationRejectionReason::IllegalGeolocation => Error::<T>::AttestationWithIllegalGeolocation,
+            AttestationRejectionReason::BeyondLocationTolerance => Error::<T>::AttestationBeyondLocationTolerance,
+            AttestationRejectionReason::TooEarly
+            | AttestationRejectionReason::TooLate => Error::<T>::AttestationBeyondTimeTolerance,
+            AttestationRejectionReason::BadSignature => Error::<T>::BadAttestationSignature,
+        }
+    }
+}
+
 impl<T: Trait> Module<T> {
 
-    fn purge_registry(cindex: CeremonyIndexType) {
-        let cids = <encointer_currencies::Module<T>>::currency_identifiers();
-        for cid in cids.iter() {
-            <ParticipantRegistry<T>>::remove_prefix((cid, cindex));
-            <ParticipantIndex<T>>::remove_prefix((cid, cindex));
-            <ParticipantCount>::insert((cid, cindex), 0);
-            <MeetupRegistry<T>>::remove_prefix((cid, cindex));
-            <MeetupIndex<T>>::remove_prefix((cid, cindex));
-            <MeetupCount>::insert((cid, cindex), 0);
-            <AttestationRegistry<T>>::remove_prefix((cid, cindex));
-            <AttestationIndex<T>>::remove_prefix((cid, cindex));
-            <AttestationCount>::insert((cid, cindex), 0);
-            <MeetupParticipantCountVote<T>>::remove_prefix((cid, cindex));
-        }
-        print_utf8(b"purged registry for last ceremony");
-    }
-
-    /* this is for a more recent revision of substrate....
-    fn random_permutation(elements: Vec<u8>) -> Vec<u8> {
-        let random_seed = <system::Module<T>>::random_seed();
-        let out = Vec::with_capacity(elements.len());
+    fn do_register_attestations(
+        sender: T::AccountId,
+        attestations: Vec<Attestation<T::Signature, T::AccountId, T::Moment>>,
+        strict: bool,
+    ) -> DispatchResult {
+        ensure!(<encointer_scheduler::Module<T>>::current_phase() == CeremonyPhaseType::ATTESTING,
+            <Error<T>>::AttestationsOutsideAttestingPhase);
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        ensure!(attestations.len()>0, <Error<T>>::NoAttestations);
+        let cid = attestations[0].claim.currency_identifier;
+        ensure!(<encointer_currencies::Module<T>>::currency_identifiers().contains(&cid),
+            <Error<T>>::InexistentCurrency);
+
+        let meetup_index = Self::meetup_index((cid, cindex), &sender);
+        let mut meetup_participants = Self::meetup_registry((cid, cindex), &meetup_index);
+        ensure!(meetup_participants.contains(&sender), <Error<T>>::NotMeetupParticipant);
+        meetup_participants.retain(|x| x != &sender);
+        let num_registered = meetup_participants.len();
+        let num_signed = attestations.len();
+        ensure!(num_signed <= num_registered, <Error<T>>::TooManyAttestations);
+        let mut verified_attestation_accounts = vec!();
+        let mut rejected_attestations = vec!();
+        let mut claim_n_participants = 0u32;
+
+        let mlocation = if let Some(l) = Self::get_meetup_location(&cid, meetup_index)
+            { l } else { return Err(<Error<T>>::MeetupLocationNotFound.into()) };
+        let mtime = if let Some(t) = Self::get_meetup_time(&cid, meetup_index)
+            { t } else { return Err(<Error<T>>::MeetupTimeCalculationError.into()) };
+        for attestation in attestations.iter() {
+            match Self::check_attestation(attestation, cid, cindex, meetup_index,
+                &meetup_participants, &mlocation, mtime) {
+                Ok(()) => {
+                    // attestation is legit. insert it!
+                    verified_attestation_accounts.insert(0, attestation.public.clone());
+                    // is it a problem if this number isn't equal for all claims? Guess not.
+                    claim_n_participants = attestation.claim.number_of_participants_confirmed;
+                }
+                Err(reason) => {
+                    if strict {
+                        return Err(<Error<T>>::from(reason).into());
+                    }
+                    rejected_attestations.push((attestation.public.clone(), reason));
+                }
+            }
+        }
+        if verified_attestation_accounts.len() == 0 {
+            // nothing is registered, so tell the client why the first attestation was rejected
+            return Err(rejected_attestations.first()
+                .map_or(<Error<T>>::NoValidAttestations, |(_, reason)| <Error<T>>::from(*reason))
+                .into());
+        }
+
+        let count = <AttestationCount>::get((cid, cindex));
+        let mut idx = count+1;
+
+        if <AttestationIndex<T>>::contains_key((cid, cindex), &sender) {
+            idx = <AttestationIndex<T>>::get((cid, cindex), &sender);
+        } else {
+            let new_count = count.checked_add(1).
+                ok_or(<Error<T>>::RegistryOverflow)?;
+            <AttestationCount>::insert((cid, cindex), new_count);
+        }
+        <AttestationRegistry<T>>::insert((cid, cindex), &idx, &verified_attestation_accounts);
+        <AttestationIndex<T>>::insert((cid, cindex), &sender, &idx);
+        <MeetupParticipantCountVote<T>>::insert((cid, cindex), &sender, &claim_n_participants);
+        print_utf8(b"registered attestations for:");
+        print_hex(&sender.encode());
+        for (attester, reason) in rejected_attestations {
+            Self::deposit_event(RawEvent::AttestationRejected(cid, sender.clone(), attester, reason));
+        }
+        let n_accepted = verified_attestation_accounts.len() as u32;
+        Self::deposit_event(RawEvent::AttestationsRegistered(cid, sender,
+            n_accepted, num_signed as u32 - n_accepted));
+        Ok(())
+    }
+
+    /// checks an attestation by a fellow meetup participant against the meetup it claims
+    fn check_attestation(
+        attestation: &Attestation<T::Signature, T::AccountId, T::Moment>,
+        cid: CurrencyIdentifier,
+        cindex: CeremonyIndexType,
+        meetup_index: MeetupIndexType,
+        meetup_participants: &[T::AccountId],
+        mlocation: &Location,
+        mtime: T::Moment,
+    ) -> Result<(), AttestationRejectionReason> {
+        use AttestationRejectionReason::*;
+        let claim = &attestation.claim;
+        ensure!(meetup_participants.contains(&attestation.public), NotAMeetupParticipant);
+        ensure!(claim.ceremony_index == cindex, WrongCeremonyIndex);
+        ensure!(claim.currency_identifier == cid, WrongCurrencyIdentifier);
+        ensure!(claim.meetup_index == meetup_index, WrongMeetupIndex);
+        ensure!(<encointer_currencies::Module<T>>::is_valid_geolocation(&claim.location),
+            IllegalGeolocation);
+        ensure!(<encointer_currencies::Module<T>>::haversine_distance(mlocation, &claim.location)
+            <= Self::location_tolerance(), BeyondLocationTolerance);
+        if let Some(dt) = mtime.checked_sub(&claim.timestamp) {
+            ensure!(dt <= Self::time_tolerance(), TooEarly);
+        } else if let Some(dt) = claim.timestamp.checked_sub(&mtime) {
+            ensure!(dt <= Self::time_tolerance(), TooLate);
+        }
+        ensure!(Self::verify_attestation_signature(attestation.clone()).is_ok(), BadSignature);
+        Ok(())
+    }
+
+    /// deletes registry entries of the ceremonies in `PendingPurges`, oldest first, followed
+    /// by expired reputation of the ceremonies in `PendingExpiries`, until `limit` entries have been deleted
+    fn purge_pending(limit: u32) -> Weight {
+        let mut purges = Self::pending_purges();
+        let mut expiries = Self::pending_expiries();
+        if purges.is_empty() && expiries.is_empty() {
+            return 0;
+        }
+        let mut removed = 0u32;
+        while !purges.is_empty() && removed < limit {
+            let (cid, cindex) = purges[0];
+            let (n, done) = Self::purge_registry_for(&cid, cindex, limit - removed);
+            removed = removed.saturating_add(n);
+            if !done {
+                break;
+            }
+            purges.remove(0);
+            print_utf8(b"purged registry of past ceremony");
+            Self::deposit_event(RawEvent::RegistryPurged(cid, cindex));
+            // reputation is kept until it can't be used for registration anymore
+            if let Some(expired) = cindex.checked_sub(T::ReputationLifetime::get()) {
+                expiries.push((cid, expired));
+            }
+        }
+        while !expiries.is_empty() && removed < limit {
+            let (cid, cindex) = expiries[0];
+            let (n, done) = Self::expire_reputation_for(&cid, cindex, limit - removed);
+            removed = removed.saturating_add(n);
+            if !done {
+                break;
+            }
+            expiries.remove(0);
+            print_utf8(b"deleted expired reputation of past ceremony");
+        }
+        <PendingPurges>::put(purges);
+        <PendingExpiries>::put(expiries);
+        T::WeightInfo::purge_registry(removed)
+    }
+
+    /// deletes up to roughly `limit` registry entries of a past ceremony by walking its
+    /// registries from the highest index downwards. a meetup is always deleted as a whole,
+    /// so the limit may be exceeded by the size of a meetup.
+    /// returns the number of deleted entries and whether the ceremony has been purged entirely
+    fn purge_registry_for(cid: &CurrencyIdentifier, cindex: CeremonyIndexType, limit: u32) -> (u32, bool) {
+        let cc = (*cid, cindex);
+        let mut removed = 0u32;
+
+        let mut n_attestations = <AttestationCount>::get(&cc);
+        while n_attestations > 0 && removed < limit {
+            <AttestationRegistry<T>>::remove(&cc, &n_attestations);
+            n_attestations -= 1;
+            removed += 1;
+        }
+        <AttestationCount>::insert(&cc, n_attestations);
+
+        let mut n_meetups = <MeetupCount>::get(&cc);
+        while n_meetups > 0 && removed < limit {
+            for p in <MeetupRegistry<T>>::take(&cc, &n_meetups) {
+                <MeetupIndex<T>>::remove(&cc, &p);
+                <AttestationIndex<T>>::remove(&cc, &p);
+                <MeetupParticipantCountVote<T>>::remove(&cc, &p);
+                removed = removed.saturating_add(1);
+            }
+            n_meetups -= 1;
+            removed = removed.saturating_add(1);
+        }
+        <MeetupCount>::insert(&cc, n_meetups);
+
+        let mut n_participants = <ParticipantCount>::get(&cc);
+        while n_participants > 0 && removed < limit {
+            let p = <ParticipantRegistry<T>>::take(&cc, &n_participants);
+            <ParticipantIndex<T>>::remove(&cc, &p);
+            <ParticipantReputationProof<T>>::remove(&cc, &p);
+            // reputation and last meetups outlive the registry, so their accounts must be remembered
+            if <ParticipantReputation<T>>::contains_key(&cc, &p) || <LastMeetup<T>>::contains_key(cid, &p) {
+                Self::add_expiring_account(&cc, &p);
+            }
+            n_participants -= 1;
+            removed = removed.saturating_add(1);
+        }
+        <ParticipantCount>::insert(&cc, n_participants);
+
+        if n_attestations > 0 || n_meetups > 0 || n_participants > 0 {
+            return (removed, false);
+        }
+        <AttestationCount>::remove(&cc);
+        <MeetupCount>::remove(&cc);
+        <ParticipantCount>::remove(&cc);
+        (removed, true)
+    }
+
+    /// deletes up to `limit` entries of a ceremony whose reputation has expired by walking its
+    /// expiring accounts from the highest index downwards. last meetups that are at least as old
+    /// as the ceremony have expired as well and are deleted along.
+    /// returns the number of deleted entries and whether the ceremony has been expired entirely
+    fn expire_reputation_for(cid: &CurrencyIdentifier, cindex: CeremonyIndexType, limit: u32) -> (u32, bool) {
+        let cc = (*cid, cindex);
+        let mut removed = 0u32;
+        let mut n_accounts = <ExpiringAccountCount>::get(&cc);
+        while n_accounts > 0 && removed < limit {
+            let p = <ExpiringAccounts<T>>::take(&cc, &n_accounts);
+            <ParticipantReputation<T>>::remove(&cc, &p);
+            <ReputationGrantApprovals<T>>::remove(&cc, &p);
+            if Self::last_meetup(cid, &p).map_or(false, |(c, _)| c <= cindex) {
+                <LastMeetup<T>>::remove(cid, &p);
+            }
+            n_accounts -= 1;
+            removed = removed.saturating_add(1);
+        }
+        if n_accounts > 0 {
+            <ExpiringAccountCount>::insert(&cc, n_accounts);
+            return (removed, false);
+        }
+        <ExpiringAccountCount>::remove(&cc);
+        <ReputationGrantCount>::remove(&cc);
+        (removed, true)
+    }
+
+    fn add_expiring_account(cc: &CurrencyCeremony, who: &T::AccountId) {
+        let n = <ExpiringAccountCount>::get(cc).saturating_add(1);
+        <ExpiringAccounts<T>>::insert(cc, &n, who);
+        <ExpiringAccountCount>::insert(cc, n);
+    }
+
+    /// deterministic Fisher-Yates shuffle driven by `seed`.
+    /// the same seed always yields the same permutation
+    fn random_permutation<E>(mut elements: Vec<E>, seed: &[u8]) -> Vec<E> {
         let n = elements.len();
-        for i in 0..n {
-            let new_random = (random_seed, i)
-                .using_encoded(|b| Blake2Hasher::hash(b))
-                .using_encoded(|mut b| u64::decode(&mut b))
+        for i in (1..n).rev() {
+            let random = u64::decode(&mut &blake2_256(&(seed, i as u64).encode())[..8])
                 .expect("Hash must be bigger than 8 bytes; Qed");
-            let elem = elements.remove(new_random % elements.len());
-            out.push(elem);
+            elements.swap(i, (random % (i as u64 + 1)) as usize);
         }
-        out
+        elements
     }
-    */
 
-    // this function is expensive, so it should later be processed off-chain within SubstraTEE-worker
-    // currently the complexity is O(n) where n is the number of registered participants
-    fn assign_meetups() {
-        let cids = <encointer_currencies::Module<T>>::currency_identifiers();
-        for cid in cids.iter() {
-            let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
-            let pcount = <ParticipantCount>::get((cid, cindex));
-
-            let mut reputables = Vec::with_capacity(pcount as usize);
-            let mut newbies = Vec::with_capacity(pcount as usize);
-
-            // TODO: upfront random permutation
-            for p in 1..=pcount {
-                let participant = <ParticipantRegistry<T>>::get((cid, cindex), &p);
-                if Self::participant_reputation((cid, cindex), &participant)
-                    == Reputation::UnverifiedReputable
-                    || <encointer_currencies::Module<T>>::bootstrappers(cid).contains(&participant)
-                {
-                    reputables.push(participant);
-                } else {
-                    newbies.push(participant);
-                }
+    /// stable sort by last meetup, so participants who met at their last meetup end up adjacent.
+    /// dealing them round-robin to meetups afterwards minimizes repeat encounters.
+    /// meetups beyond the reputation lifetime are ignored, just like the reputation they earned
+    fn group_by_last_meetup(cid: &CurrencyIdentifier, participants: Vec<T::AccountId>) -> Vec<T::AccountId> {
+        let oldest = <encointer_scheduler::Module<T>>::current_ceremony_index()
+            .saturating_sub(T::ReputationLifetime::get());
+        let mut tagged: Vec<_> = participants
+            .into_iter()
+            .map(|p| (Self::last_meetup(cid, &p).filter(|(c, _)| *c >= oldest), p))
+            .collect();
+        tagged.sort_by_key(|(m, _)| *m);
+        tagged.into_iter().map(|(_, p)| p).collect()
+    }
+
+    /// splits reputables and newbies into at most `max_meetups` meetups of
+    /// MeetupMinSize..=MeetupMaxSize participants, seating as many registrants as possible
+    /// while respecting the newbie ratio in every meetup.
+    /// among equally good options, fewer and larger meetups are preferred.
+    /// returns the number of (reputables, newbies) for each meetup
+    fn meetup_compositions(n_reputables: u64, n_newbies: u64, max_meetups: u64) -> Vec<(u64, u64)> {
+        let max_size = T::MeetupMaxSize::get().max(1);
+        let min_size = T::MeetupMinSize::get();
+        let ratio = T::NewbieRatio::get();
+        let n_reputables = min(n_reputables, max_meetups.saturating_mul(max_size));
+        // how many newbies a meetup with r reputables can take
+        let newbie_cap = |r: u64| {
+            if ratio > 1 {
+                min(r / (ratio - 1), max_size.saturating_sub(r))
+            } else {
+                0
             }
-            let mut n = reputables.len();
-            n += min(newbies.len(), n / 4);
-            let n_meetups = n / 12 + 1;
-            let mut meetups = Vec::with_capacity(n_meetups);
-            let mut meetup_n_rep = vec![0; n_meetups];
-            for _i in 0..n_meetups {
-                meetups.push(Vec::with_capacity(12))
-            }
-            // first, evenly assign reputables to meetups
-            for (i, p) in reputables.iter().enumerate() {
-                meetups[i % n_meetups].push(p);
-                meetup_n_rep[i % n_meetups] += 1;
-            }
-            // now, distribute newbies, complying with newbie limit per meetup
-            // FIXME: stop after skipping n_meetups newbies
-            for (i, p) in newbies.iter().enumerate() {
-                let _idx = i % n_meetups;
-                if meetups[_idx].len() < meetup_n_rep[_idx] * 4 / 3 {
-                    meetups[i % n_meetups].push(p);
-                } else {
-                    print_utf8(b"had to skip one newbie");
-                }
+        };
+        // fewest meetups that can seat all reputables
+        let k_min = (n_reputables + max_size - 1) / max_size;
+        // meetups with this many reputables still have room for a full share of newbies.
+        // more meetups than that would not increase the newbie capacity
+        let r_full = (if ratio > 1 { max_size * (ratio - 1) / ratio } else { max_size }).max(1);
+        let k_full = (n_reputables + r_full - 1) / r_full;
+        // (number of meetups, number of seated newbies)
+        let mut best: Option<(u64, u64)> = None;
+        for k in k_min.max(1)..=min(k_full, max_meetups) {
+            let base = n_reputables / k;
+            let extra = n_reputables % k;
+            let capacity = extra * newbie_cap(base + 1) + (k - extra) * newbie_cap(base);
+            let seated = min(n_newbies, capacity);
+            // newbies are dealt evenly, so the smallest meetup gets at least this many participants
+            if base + min(newbie_cap(base), seated / k) < min_size {
+                continue;
             }
-            // purge meetups that are too small
-            let mut toosmall = Vec::with_capacity(n_meetups);
-            for (i, m) in meetups.iter().enumerate() {
-                if m.len() < 3 {
-                    toosmall.push(i);
-                    print_utf8(b"one meetup can't take place because it is too small");
-                }
+            if best.map_or(true, |(_, s)| seated > s) {
+                best = Some((k, seated));
             }
-            for i in toosmall {
-                meetups.remove(i);
+        }
+        let (k, seated) = match best {
+            Some(b) => b,
+            None => return vec![],
+        };
+        let mut compositions: Vec<(u64, u64)> = (0..k)
+            .map(|i| (n_reputables / k + if i < n_reputables % k { 1 } else { 0 }, 0))
+            .collect();
+        let mut remaining = seated;
+        while remaining > 0 {
+            for c in compositions.iter_mut() {
+                if remaining > 0 && c.1 < newbie_cap(c.0) {
+                    c.1 += 1;
+                    remaining -= 1;
+                }
             }
-            // FIXME: with nightly we could do: meetups.drain_filter(|x| x.len() < 3).collect::<Vec<_>>();
+        }
+        compositions
+    }
 
-            if !meetups.is_empty() {
-                // commit result to state
-                <MeetupCount>::insert((cid, cindex), n_meetups as MeetupIndexType);
-                for (i, m) in meetups.iter().enumerate() {
-                    let _idx = (i + 1) as MeetupIndexType;
-                    for p in meetups[i].iter() {
-                        <MeetupIndex<T>>::insert((cid, cindex), p, &_idx);
+    /// deals participants round-robin to meetups, taking at most `seats[i]` into meetup i.
+    /// returns the number of participants that could not be seated
+    fn deal_round_robin(
+        meetups: &mut Vec<Vec<T::AccountId>>,
+        participants: Vec<T::AccountId>,
+        mut seats: Vec<u64>,
+    ) -> usize {
+        let mut participants = participants.into_iter();
+        loop {
+            let mut dealt = false;
+            for (i, m) in meetups.iter_mut().enumerate() {
+                if seats[i] == 0 {
+                    continue;
+                }
+                match participants.next() {
+                    Some(p) => {
+                        m.push(p);
+                        seats[i] -= 1;
+                        dealt = true;
                     }
-                    <MeetupRegistry<T>>::insert((cid, cindex), &_idx, m.clone());
+                    None => return 0,
                 }
-            };
+            }
+            if !dealt {
+                return participants.count();
+            }
+        }
+    }
+
+    /// assigns the registered participants of a currency to meetups. they are shuffled and split
+    /// into meetups at once, and the meetups are seated one after the other until `budget` is
+    /// exhausted. at least one step is done per call, so assignment always progresses.
+    /// returns the consumed weight and whether all meetups have been seated
+    fn assign_meetups_for(cid: &CurrencyIdentifier, cindex: CeremonyIndexType, budget: Weight) -> (Weight, bool) {
+        let mut consumed: Weight = 0;
+        let mut seated = match Self::assignment_cursor((cid, cindex)) {
+            Some(s) => s,
+            None => {
+                consumed = Self::compose_meetups(cid, cindex);
+                0
+            }
+        };
+        let meetup_count = Self::meetup_count((cid, cindex));
+        while seated < meetup_count {
+            if consumed >= budget && consumed > 0 {
+                <AssignmentCursor>::insert((cid, cindex), seated);
+                return (consumed, false);
+            }
+            seated += 1;
+            consumed = consumed.saturating_add(Self::seat_meetup(cid, cindex, seated));
         }
-        print_utf8(b"assigned meetups");
+        <AssignmentCursor>::remove((cid, cindex));
+        (consumed, true)
+    }
+
+    // this function is expensive, so it should later be processed off-chain within SubstraTEE-worker
+    // currently the complexity is O(n log(n)) where n is the number of registered participants.
+    // it only writes one registry entry per meetup, seating the participants is left to `seat_meetup`
+    fn compose_meetups(cid: &CurrencyIdentifier, cindex: CeremonyIndexType) -> Weight {
+        let pcount = <ParticipantCount>::get((cid, cindex));
+        let bootstrappers = <encointer_currencies::Module<T>>::bootstrappers(cid);
+
+        let mut reputables = Vec::with_capacity(pcount as usize);
+        let mut newbies = Vec::with_capacity(pcount as usize);
+
+        for p in 1..=pcount {
+            let participant = <ParticipantRegistry<T>>::get((cid, cindex), &p);
+            // registration is final now, so linked reputation can be completed
+            Self::complete_reputation_link(cid, cindex, &participant);
+            if Self::participant_reputation((cid, cindex), &participant)
+                == Reputation::UnverifiedReputable
+                || bootstrappers.contains(&participant)
+            {
+                reputables.push(participant);
+            } else {
+                newbies.push(participant);
+            }
+        }
+        // upfront random permutation so registration order doesn't decide who meets whom
+        let seed = T::RandomnessSource::random(&(cid, cindex).encode());
+        let reputables = Self::random_permutation(reputables, &(seed, 0u8).encode());
+        let newbies = Self::random_permutation(newbies, &(seed, 1u8).encode());
+        // whitepaper III-B Rule 1: minimize the number of participants that have met at previous ceremony
+        let reputables = Self::group_by_last_meetup(cid, reputables);
+        let newbies = Self::group_by_last_meetup(cid, newbies);
+        // whitepaper III-B Rule 2: maximize number of participants per meetup within 3<=N<=12
+        // (the bounds are configurable)
+        // there can't be more meetups than locations
+        let n_locations = <encointer_currencies::Module<T>>::locations(cid).len() as u64;
+        let (n_reputables, n_newbies) = (reputables.len() as u64, newbies.len() as u64);
+        let compositions = Self::meetup_compositions(n_reputables, n_newbies, n_locations);
+        let n_seated = |c: &Vec<(u64, u64)>| c.iter().map(|(r, n)| r + n).sum::<u64>();
+        let n_unseated = n_seated(&Self::meetup_compositions(n_reputables, n_newbies, MeetupIndexType::max_value()))
+            .saturating_sub(n_seated(&compositions));
+        if n_unseated > 0 {
+            Self::deposit_event(RawEvent::MeetupLocationsExhausted(*cid, n_unseated));
+        }
+        let mut meetups: Vec<Vec<T::AccountId>> = compositions
+            .iter()
+            .map(|(r, n)| Vec::with_capacity((r + n) as usize))
+            .collect();
+        let skipped = Self::deal_round_robin(&mut meetups, reputables, compositions.iter().map(|c| c.0).collect())
+            + Self::deal_round_robin(&mut meetups, newbies, compositions.iter().map(|c| c.1).collect());
+        if skipped > 0 {
+            print_utf8(b"had to skip participants");
+        }
+        Self::commit_meetups(cid, cindex, meetups);
+        T::WeightInfo::compose_meetups(pcount.try_into().unwrap_or(u32::max_value()))
+    }
+
+    /// hands the meetup and reputation histories of the former key a participant has linked
+    /// reputation from over to the participant. this is deferred until registration is final,
+    /// so unregistering only needs to revert the burn of the linked attendance and no history
+    /// is truncated for a link that is reverted
+    fn complete_reputation_link(cid: &CurrencyIdentifier, cindex: CeremonyIndexType, participant: &T::AccountId) {
+        let attendee = match Self::participant_reputation_proof((cid, cindex), participant) {
+            Some((_, attendee)) => attendee,
+            None => return,
+        };
+        if let Some(m) = <LastMeetup<T>>::take(cid, &attendee) {
+            // the participant may have attended more recently with its own key
+            if Self::last_meetup(cid, participant).map_or(true, |(c, _)| c < m.0) {
+                <LastMeetup<T>>::insert(cid, participant, m);
+            }
+        }
+        Self::move_reputation_history(&attendee, participant);
+    }
+
+    /// purges meetups that are too small and writes the remaining ones to the meetup registry.
+    /// meetup indices are contiguous, starting at 1, so meetup `i` takes place at
+    /// the `i`-th location of the currency (see `get_meetup_location`)
+    fn commit_meetups(cid: &CurrencyIdentifier, cindex: CeremonyIndexType, mut meetups: Vec<Vec<T::AccountId>>) {
+        let n_assigned = meetups.len();
+        meetups.retain(|m| m.len() as u64 >= T::MeetupMinSize::get());
+        if meetups.len() < n_assigned {
+            print_utf8(b"some meetups can't take place because they are too small");
+        }
+        <MeetupCount>::insert((cid, cindex), meetups.len() as MeetupIndexType);
+        let n_seated = meetups.iter().map(|m| m.len() as ParticipantIndexType).sum();
+        Self::deposit_event(RawEvent::MeetupsAssigned(*cid, meetups.len() as MeetupIndexType, n_seated));
+        for (i, m) in meetups.into_iter().enumerate() {
+            <MeetupRegistry<T>>::insert((cid, cindex), &((i + 1) as MeetupIndexType), m);
+        }
+    }
+
+    /// lets the participants of a committed meetup know where they meet
+    fn seat_meetup(cid: &CurrencyIdentifier, cindex: CeremonyIndexType, m: MeetupIndexType) -> Weight {
+        let participants = Self::meetup_registry((cid, cindex), &m);
+        for p in participants.iter() {
+            <MeetupIndex<T>>::insert((cid, cindex), p, &m);
+            <LastMeetup<T>>::insert(cid, p, (cindex, m));
+        }
+        T::WeightInfo::seat_meetup(participants.len().try_into().unwrap_or(u32::max_value()))
     }
 
     fn verify_attestation_signature(
@@ -427,7 +1209,7 @@ impl<T: Trait> Module<T> {
     ) -> DispatchResult {
         ensure!(
             attestation.public != attestation.claim.claimant_public,
-            "attestation may not be self-signed"
+            <Error<T>>::SelfSignedAttestation
         );
         match attestation
             .signature
@@ -448,74 +1230,173 @@ impl<T: Trait> Module<T> {
         }
     }
 
-    // this function takes O(n) for n meetups, so it should later be processed off-chain within
-    // SubstraTEE-worker together with the entire registry
-    // as this function can only be called by the ceremony state machine, it could actually work out fine
-    // on-chain. It would just delay the next block once per ceremony cycle.
-    fn issue_rewards() {
-        if <encointer_scheduler::Module<T>>::current_phase() != CeremonyPhaseType::REGISTERING {
-            return;
+    /// issues the rewards of past ceremonies in `PendingRewards`, one meetup after the other,
+    /// until `budget` is exhausted. at least one meetup is evaluated per call, so issuance always
+    /// progresses. once all meetups of a ceremony are evaluated, its registry is queued for purging
+    fn issue_pending_rewards(budget: Weight) -> Weight {
+        let mut queue = Self::pending_rewards();
+        if queue.is_empty() {
+            return 0;
         }
-        let cids = <encointer_currencies::Module<T>>::currency_identifiers();
-        for cid in cids.iter() {
-            let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index() -1;
+        let mut consumed: Weight = 0;
+        while !queue.is_empty() {
+            let (cid, cindex) = queue[0];
             let meetup_count = Self::meetup_count((cid, cindex));
-            let reward = Self::ceremony_reward();
-
-            for m in 1..=meetup_count {
-                // first, evaluate votes on how many participants showed up
-                let (n_confirmed, n_honest_participants) = match Self::ballot_meetup_n_votes(cid, cindex, m)
-                {
-                    Some(nn) => nn,
-                    _ => {
-                        print_utf8(b"skipping meetup because votes for num of participants are not dependable");
-                        continue;
-                    }
+            let mut evaluated = Self::reward_cursor((cid, cindex));
+            while evaluated < meetup_count {
+                if consumed >= budget && consumed > 0 {
+                    <RewardCursor>::insert((cid, cindex), evaluated);
+                    <PendingRewards>::put(queue);
+                    return consumed;
+                }
+                evaluated += 1;
+                consumed = consumed.saturating_add(Self::issue_rewards_for_meetup(&cid, cindex, evaluated));
+            }
+            <RewardCursor>::remove((cid, cindex));
+            queue.remove(0);
+            print_utf8(b"issued rewards for past ceremony");
+            Self::deposit_event(RawEvent::RewardsFinalized(cid, cindex));
+            // the registry is needed to evaluate meetups, so it can only be purged now
+            <PendingPurges>::mutate(|q| q.push((cid, cindex)));
+        }
+        <PendingRewards>::kill();
+        consumed
+    }
+
+    // evaluating a meetup takes O(n^2) for n participants. meetups are small, so this is fine on-chain
+    fn issue_rewards_for_meetup(cid: &CurrencyIdentifier, cindex: CeremonyIndexType, m: MeetupIndexType) -> Weight {
+        let reward = Self::reward_for(cid);
+        let weight = T::WeightInfo::issue_rewards(
+            Self::meetup_registry((cid, cindex), &m).len().try_into().unwrap_or(u32::max_value()));
+        // first, evaluate votes on how many participants showed up
+        let (n_confirmed, n_honest_participants) = match Self::ballot_meetup_n_votes(cid, cindex, m)
+        {
+            Some(nn) => nn,
+            _ => {
+                print_utf8(b"skipping meetup because votes for num of participants are not dependable");
+                let reason = if Self::meetup_registry((cid, cindex), &m).iter()
+                    .any(|p| <AttestationIndex<T>>::contains_key((cid, cindex), p)) {
+                    MeetupSkipReason::VotesNotDependable
+                } else {
+                    MeetupSkipReason::NoAttestations
                 };
-                let meetup_participants = Self::meetup_registry((cid, cindex), &m);
-                for p in meetup_participants {
-                    if Self::meetup_participant_count_vote((cid, cindex), &p) != n_confirmed {
-                        print_utf8(b"skipped participant because of wrong participant count vote");
-                        continue;
-                    }
-                    let attestations = Self::attestation_registry(
-                        (cid, cindex),
-                        &Self::attestation_index((cid, cindex), &p),
-                    );
-                    if attestations.len() < (n_honest_participants - 1) as usize
-                        || attestations.is_empty()
-                    {
-                        print_utf8(b"skipped participant because of too few attestations");
-                        continue;
-                    }
-                    let mut has_attested = 0u32;
-                    for w in attestations {
-                        let w_attestations = Self::attestation_registry(
-                            (cid, cindex),
-                            &Self::attestation_index((cid, cindex), &w),
-                        );
-                        if w_attestations.contains(&p) {
-                            has_attested += 1;
-                        }
-                    }
-                    if has_attested < (n_honest_participants - 1) {
-                        print_utf8(b"skipped participant because didn't testify for honest peers");
-                        continue;
-                    }
-                    // TODO: check that p also signed others
-                    // participant merits reward
-                    print_utf8(b"participant merits reward");
-                    if let Ok(_) = <encointer_balances::Module<T>>::issue(*cid, &p, reward) {
-                        <ParticipantReputation<T>>::insert(
-                            (cid, cindex),
-                            &p,
-                            Reputation::VerifiedUnlinked,
-                        );
+                Self::deposit_event(RawEvent::MeetupSkipped(*cid, m, reason));
+                return weight;
+            }
+        };
+        let meetup_participants = Self::meetup_registry((cid, cindex), &m);
+        // the attestation graph of the meetup: attesters[i] have attested meetup_participants[i]
+        let attesters: Vec<Vec<T::AccountId>> = meetup_participants.iter()
+            .map(|p| Self::attestation_registry((cid, cindex), &Self::attestation_index((cid, cindex), p)))
+            .collect();
+        let is_honest: Vec<bool> = meetup_participants.iter()
+            .map(|p| Self::meetup_participant_count_vote((cid, cindex), p) == n_confirmed)
+            .collect();
+        let mutual = |a: usize, b: usize| attesters[a].contains(&meetup_participants[b])
+            && attesters[b].contains(&meetup_participants[a]);
+        // when two participants lack a mutual attestation, it can't be told whether one of them
+        // refused to attest the other or withheld the other's attestation from their claim.
+        // so the honest voters with the fewest mutual attestations are dropped until the
+        // remaining ones have all attested each other
+        let mut trusted: Vec<usize> = (0..meetup_participants.len()).filter(|&i| is_honest[i]).collect();
+        loop {
+            let degrees: Vec<usize> = trusted.iter()
+                .map(|&a| trusted.iter().filter(|&&b| b != a && mutual(a, b)).count())
+                .collect();
+            let min_degree = match degrees.iter().min() {
+                Some(d) => *d,
+                None => break,
+            };
+            if min_degree + 1 >= trusted.len() {
+                break;
+            }
+            trusted = trusted.iter()
+                .zip(degrees.iter())
+                .filter(|(_, d)| **d > min_degree)
+                .map(|(a, _)| *a)
+                .collect();
+        }
+        for (i, p) in meetup_participants.iter().enumerate() {
+            if !is_honest[i] {
+                print_utf8(b"skipped participant because of wrong participant count vote");
+                continue;
+            }
+            if attesters[i].len() < (n_honest_participants - 1) as usize
+                || attesters[i].is_empty()
+            {
+                print_utf8(b"skipped participant because of too few attestations");
+                continue;
+            }
+            let has_attested = (0..meetup_participants.len())
+                .filter(|&j| j != i && mutual(i, j))
+                .count() as u32;
+            if has_attested < (n_honest_participants - 1) {
+                print_utf8(b"skipped participant because didn't testify for honest peers");
+                continue;
+            }
+            if !trusted.contains(&i) {
+                print_utf8(b"skipped participant because of missing mutual attestations with honest peers");
+                continue;
+            }
+            // participant merits reward
+            print_utf8(b"participant merits reward");
+            if let Ok(_) = <encointer_balances::Module<T>>::issue(*cid, p, reward) {
+                <ParticipantReputation<T>>::insert(
+                    (cid, cindex),
+                    p,
+                    Reputation::VerifiedUnlinked,
+                );
+                Self::record_attendance(p, (*cid, cindex));
+                Self::deposit_event(RawEvent::RewardIssued(*cid, p.clone(), reward));
+            }
+        }
+        weight
+    }
+
+    /// does the work caused by the phase changes in `PendingPhaseChanges`, oldest first, one currency
+    /// after the other, until `budget` is exhausted. stores where to resume. at least one step is
+    /// done per call, so the work always progresses
+    fn process_pending_phase_changes(budget: Weight) -> Weight {
+        let mut queue = Self::pending_phase_changes();
+        if queue.is_empty() {
+            return 0;
+        }
+        let cids = <encointer_currencies::Module<T>>::currency_identifiers();
+        let mut consumed: Weight = 0;
+        while !queue.is_empty() {
+            let (phase, cindex, cursor) = queue[0];
+            let mut next = cursor as usize;
+            while next < cids.len() {
+                if consumed >= budget && consumed > 0 {
+                    queue[0].2 = next as u32;
+                    <PendingPhaseChanges>::put(queue);
+                    return consumed;
+                }
+                let cid = &cids[next];
+                let (weight, done) = match phase {
+                    CeremonyPhaseType::ASSIGNING =>
+                        Self::assign_meetups_for(cid, cindex, budget.saturating_sub(consumed)),
+                    CeremonyPhaseType::ATTESTING => (0, true),
+                    CeremonyPhaseType::REGISTERING => {
+                        // rewards are issued incrementally, see `issue_pending_rewards`
+                        <PendingRewards>::mutate(|q| q.push((*cid, cindex - 1)));
+                        (0, true)
                     }
+                };
+                consumed = consumed.saturating_add(weight);
+                if done {
+                    next += 1;
                 }
             }
+            queue.remove(0);
+            match phase {
+                CeremonyPhaseType::ASSIGNING => print_utf8(b"assigned meetups"),
+                CeremonyPhaseType::ATTESTING => (),
+                CeremonyPhaseType::REGISTERING => print_utf8(b"queued reward issuance for last ceremony"),
+            }
         }
-        print_utf8(b"issued reward");
+        <PendingPhaseChanges>::kill();
+        consumed
     }
 
     fn ballot_meetup_n_votes(
@@ -541,7 +1422,7 @@ impl<T: Trait> Module<T> {
         }
         // sort by descending vote count
         n_vote_candidates.sort_by(|a, b| b.1.cmp(&a.1));
-        if n_vote_candidates[0].1 < 3 {
+        if n_vote_candidates[0].1 < T::MeetupQuorum::get() {
             return None;
         }
         Some(n_vote_candidates[0])
@@ -552,7 +1433,7 @@ impl<T: Trait> Module<T> {
         meetup_idx: MeetupIndexType,        
     ) -> Option<Location> {
         let locations = <encointer_currencies::Module<T>>::locations(&cid);
-        if meetup_idx <= locations.len() as MeetupIndexType {
+        if meetup_idx > 0 && meetup_idx <= locations.len() as MeetupIndexType {
             Some(locations[(meetup_idx - 1) as usize])
         } else {
             None
@@ -582,6 +1463,166 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// adds a verified attendance to an account's reputation history. reputation may be
+    /// granted for an earlier ceremony than the latest attendance, so the history is kept sorted
+    fn record_attendance(who: &T::AccountId, cc: CurrencyCeremony) {
+        <ReputationHistory<T>>::mutate(who, |h| {
+            if h.contains(&cc) {
+                return;
+            }
+            h.push(cc);
+            h.sort_by_key(|(_, cindex)| *cindex);
+            Self::truncate_reputation_history(h);
+        });
+    }
+
+    /// merges the reputation history of a linked former key into the one of its new key
+    fn move_reputation_history(from: &T::AccountId, to: &T::AccountId) {
+        let history = <ReputationHistory<T>>::take(from);
+        if history.is_empty() {
+            return;
+        }
+        <ReputationHistory<T>>::mutate(to, |h| {
+            h.extend(history);
+            h.sort_by_key(|(cid, cindex)| (*cindex, *cid));
+            h.dedup();
+            Self::truncate_reputation_history(h);
+        });
+    }
+
+    /// drops the oldest attendances beyond `ReputationHistoryLength`
+    fn truncate_reputation_history(history: &mut Vec<CurrencyCeremony>) {
+        let max = T::ReputationHistoryLength::get() as usize;
+        if history.len() > max {
+            history.drain(..history.len() - max);
+        }
+    }
+
+    /// whether a currency accepts proofs of attendance of another currency for registration
+    pub fn accepts_reputation_of(cid: &CurrencyIdentifier, other: &CurrencyIdentifier) -> bool {
+        if cid == other {
+            return true;
+        }
+        match Self::foreign_reputation_policy(cid) {
+            ForeignReputationPolicy::AcceptAll => true,
+            ForeignReputationPolicy::LocalOnly => false,
+            ForeignReputationPolicy::Whitelist(whitelist) => whitelist.contains(other),
+        }
+    }
+
+    /// whether reputation of a past ceremony can still be used for registration
+    fn is_within_reputation_lifetime(cindex: CeremonyIndexType) -> bool {
+        let current = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        cindex < current && cindex >= current.saturating_sub(T::ReputationLifetime::get())
+    }
+
+    fn do_grant_reputation(cid: CurrencyIdentifier, cindex: CeremonyIndexType, reputable: T::AccountId) {
+        // accounts with pending approvals are already known
+        if !<ParticipantReputation<T>>::contains_key(&(cid, cindex), &reputable)
+            && !<ReputationGrantApprovals<T>>::contains_key(&(cid, cindex), &reputable)
+        {
+            Self::add_expiring_account(&(cid, cindex), &reputable);
+        }
+        <ParticipantReputation<T>>::insert(&(cid, cindex), &reputable, Reputation::VerifiedUnlinked);
+        // granted reputation counts like a verified attendance
+        Self::record_attendance(&reputable, (cid, cindex));
+        print_utf8(b"granting reputation to:");
+        print_hex(&reputable.encode());
+        Self::deposit_event(RawEvent::ReputationGranted(cid, cindex, reputable));
+    }
+
+    /// whether an account's attendance at a completed ceremony has been verified or granted.
+    /// the reputation history survives registry purging. the registry covers what the history
+    /// doesn't know within the reputation lifetime (see `ReputationProvider`)
+    pub fn is_verified_at(cid: &CurrencyIdentifier, who: &T::AccountId, cindex: CeremonyIndexType) -> bool {
+        cindex < <encointer_scheduler::Module<T>>::current_ceremony_index()
+            && (Self::reputation_history(who).contains(&(*cid, cindex))
+                || Self::is_verified_in_registry(cid, who, cindex))
+    }
+
+    /// whether an account has been verified in any of the last `within_ceremonies` completed ceremonies
+    pub fn is_verified(cid: &CurrencyIdentifier, who: &T::AccountId, within_ceremonies: CeremonyIndexType) -> bool {
+        let current = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        let oldest = current.saturating_sub(within_ceremonies);
+        Self::reputation_history(who)
+            .iter()
+            .any(|(c, i)| c == cid && *i >= oldest && *i < current)
+            // only the reputation lifetime needs to be looked up in the registry
+            || (oldest.max(current.saturating_sub(T::ReputationLifetime::get()))..current)
+                .any(|i| Self::is_verified_in_registry(cid, who, i))
+    }
+
+    /// whether the registry knows an account's attendance at a ceremony as verified,
+    /// no matter whether it has been linked since
+    fn is_verified_in_registry(cid: &CurrencyIdentifier, who: &T::AccountId, cindex: CeremonyIndexType) -> bool {
+        Self::is_within_reputation_lifetime(cindex)
+            && Self::participant_reputation((cid, cindex), who).is_verified()
+    }
+
+    /// in how many of the last `n` completed ceremonies an account's attendance has been verified.
+    /// if `cid` is given, only attendances in that currency count
+    pub fn attendance_count(who: &T::AccountId, cid: Option<CurrencyIdentifier>, n: CeremonyIndexType) -> u32 {
+        let oldest = <encointer_scheduler::Module<T>>::current_ceremony_index().saturating_sub(n);
+        Self::attended_ceremonies(who, cid)
+            .iter()
+            .filter(|i| **i >= oldest)
+            .count() as u32
+    }
+
+    /// the completed ceremonies an account has attended, in ascending order.
+    /// if `cid` is given, only attendances in that currency count
+    fn attended_ceremonies(who: &T::AccountId, cid: Option<CurrencyIdentifier>) -> Vec<CeremonyIndexType> {
+        let current = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        let mut attended: Vec<CeremonyIndexType> = Self::reputation_history(who)
+            .into_iter()
+            .filter(|(c, i)| *i < current && cid.map_or(true, |cid| cid == *c))
+            .map(|(_, i)| i)
+            .collect();
+        // attending in several currencies during the same ceremony counts once
+        attended.sort();
+        attended.dedup();
+        attended
+    }
+
+    /// sum of the weights of all attended ceremonies. an attendance at the last ceremony weighs
+    /// `FULL_ATTENDANCE_SCORE`, and weight decays by `ReputationDecay` with every ceremony since
+    pub fn reputation_score(who: &T::AccountId, cid: Option<CurrencyIdentifier>) -> u64 {
+        let last = <encointer_scheduler::Module<T>>::current_ceremony_index().saturating_sub(1);
+        let decay = T::ReputationDecay::get();
+        Self::attended_ceremonies(who, cid)
+            .into_iter()
+            .map(|i| decay.saturating_pow(last.saturating_sub(i) as usize) * FULL_ATTENDANCE_SCORE)
+            .fold(0u64, |score, w| score.saturating_add(w))
+    }
+
+    /// whether an account has attended at least `k` of the last `n` completed ceremonies
+    pub fn has_attended(who: &T::AccountId, cid: Option<CurrencyIdentifier>, k: u32, n: CeremonyIndexType) -> bool {
+        Self::attendance_count(who, cid, n) >= k
+    }
+
+    /// the reward for attending a ceremony of a currency
+    pub fn reward_for(cid: &CurrencyIdentifier) -> BalanceType {
+        Self::currency_ceremony_reward(cid).unwrap_or_else(Self::ceremony_reward)
+    }
+
+    /// the meetup a participant has been assigned to in the current ceremony. 0 if unassigned
+    pub fn get_meetup_index(cid: &CurrencyIdentifier, participant: &T::AccountId) -> MeetupIndexType {
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        Self::meetup_index((cid, cindex), participant)
+    }
+
+    /// the fellow participants of the meetup a participant has been assigned to in the current ceremony
+    pub fn get_meetup_participants(cid: &CurrencyIdentifier, participant: &T::AccountId) -> Vec<T::AccountId> {
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        let meetup_index = Self::meetup_index((cid, cindex), participant);
+        if meetup_index == 0 {
+            return vec!();
+        }
+        let mut participants = Self::meetup_registry((cid, cindex), meetup_index);
+        participants.retain(|p| p != participant);
+        participants
+    }
+
     #[cfg(test)]
     // only to be used by tests
     fn fake_reputation(cidcindex: CurrencyCeremony, account: &T::AccountId, rep: Reputation) {
@@ -589,22 +1630,36 @@ impl<T: Trait> Module<T> {
     }
 }
 
+impl<T: Trait> ReputationProvider<T::AccountId> for Module<T> {
+    fn is_verified_at(cid: &CurrencyIdentifier, who: &T::AccountId, cindex: CeremonyIndexType) -> bool {
+        Self::is_verified_at(cid, who, cindex)
+    }
+
+    fn is_verified(cid: &CurrencyIdentifier, who: &T::AccountId, within_ceremonies: CeremonyIndexType) -> bool {
+        Self::is_verified(cid, who, within_ceremonies)
+    }
+
+    fn attendance_count(who: &T::AccountId, cid: Option<CurrencyIdentifier>, n: CeremonyIndexType) -> u32 {
+        Self::attendance_count(who, cid, n)
+    }
+
+    fn reputation_score(who: &T::AccountId, cid: Option<CurrencyIdentifier>) -> u64 {
+        Self::reputation_score(who, cid)
+    }
+}
+
 impl<T: Trait> OnCeremonyPhaseChange for Module<T> {
     fn on_ceremony_phase_change(new_phase: CeremonyPhaseType) 
     { 
-        match new_phase {
-            CeremonyPhaseType::ASSIGNING => {
-                Self::assign_meetups();
-            }
-            CeremonyPhaseType::ATTESTING => { }
-            CeremonyPhaseType::REGISTERING => { 
-                Self::issue_rewards();
-                let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
-                Self::purge_registry(cindex-1);
-            }
-        }
+        // the work itself is done in `on_initialize`, where its weight can be accounted for.
+        // work of earlier phase changes that is still pending comes first
+        let cindex = <encointer_scheduler::Module<T>>::current_ceremony_index();
+        <PendingPhaseChanges>::mutate(|q| q.push((new_phase, cindex, 0)));
     }
 }
 
 #[cfg(test)]
 mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;